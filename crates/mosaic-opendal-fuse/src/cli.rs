@@ -1,7 +1,7 @@
 use core::fmt;
 use std::fmt::Display;
 
-use clap::{Args, Parser};
+use clap::{Args, Parser, ValueEnum};
 use nix::unistd::{Gid, Uid};
 
 /// Top-level CLI struct for the binary.
@@ -16,15 +16,44 @@ pub(crate) struct Cli {
     #[command(flatten)]
     pub mount_options: CliMountOptions,
 
+    /// Filesystem subtype, shown as `fuse.<subtype>` in `mount` output. Added to the
+    /// `--custom-options`, if any
+    #[arg(long)]
+    pub subtype: Option<String>,
+
     /// The path to listen on for socket connections
     #[arg(short, long, default_value = "/tmp/mosaic_opendal_fuse.sock")]
     pub socket: String,
 
+    /// Permissions of the socket in octal, e.g. `660`. Defaults to the permissions it is
+    /// created with
+    #[arg(long, value_parser = parse_socket_mode)]
+    pub socket_mode: Option<u32>,
+
+    /// The format of the log output
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Skip checking `/etc/fuse.conf` for `user_allow_other` when `--allow-other` or
+    /// `--allow-root` is passed
+    #[arg(long, default_value_t = false)]
+    pub skip_fuse_conf_check: bool,
+
     /// Whether to use an in-memory operator instead of an actual S3 operator, for testing
     #[arg(long, hide = true)]
     pub in_memory: bool,
 }
 
+/// The format of the log output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, e.g. for log aggregation.
+    Json,
+}
+
 /// CLI representation of FUSE mount options.
 #[derive(Debug, Clone, Default, Args)]
 pub(crate) struct CliMountOptions {
@@ -94,6 +123,23 @@ pub(crate) struct CliMountOptions {
     pub custom_options: Option<String>,
 }
 
+impl Cli {
+    /// Takes the subtype to set on the configuration. fuse3 only supports a single custom
+    /// options string, which setting the subtype on the configuration replaces, so with
+    /// `--custom-options` the subtype is appended to those instead and `None` is returned.
+    pub(crate) fn take_subtype(&mut self) -> Option<String> {
+        let subtype = self.subtype.take()?;
+        match &mut self.mount_options.custom_options {
+            Some(options) if !options.is_empty() => {
+                options.push_str(",subtype=");
+                options.push_str(&subtype);
+                None
+            }
+            _ => Some(subtype),
+        }
+    }
+}
+
 impl From<CliMountOptions> for fuse3::MountOptions {
     fn from(cli: CliMountOptions) -> Self {
         let mut m = fuse3::MountOptions::default();
@@ -143,3 +189,92 @@ fn default_uid() -> u32 {
 fn default_gid() -> u32 {
     Gid::current().as_raw()
 }
+
+/// Parses an octal permission mode such as `660` or `0o660`.
+fn parse_socket_mode(s: &str) -> Result<u32, String> {
+    let digits = s.strip_prefix("0o").unwrap_or(s);
+    let mode = u32::from_str_radix(digits, 8).map_err(|_| format!("`{s}` is not an octal mode"))?;
+    if mode > 0o7777 {
+        return Err(format!("`{s}` is not a valid mode, the maximum is 7777"));
+    }
+    Ok(mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_socket_mode_octal() {
+        assert_eq!(parse_socket_mode("660"), Ok(0o660));
+        assert_eq!(parse_socket_mode("0640"), Ok(0o640));
+        assert_eq!(parse_socket_mode("0o600"), Ok(0o600));
+        assert_eq!(parse_socket_mode("7777"), Ok(0o7777));
+    }
+
+    #[test]
+    fn parse_socket_mode_rejects_invalid() {
+        assert!(parse_socket_mode("").is_err());
+        assert!(parse_socket_mode("rw-r-----").is_err());
+        assert!(parse_socket_mode("680").is_err());
+        assert!(parse_socket_mode("17777").is_err());
+        assert!(parse_socket_mode("-640").is_err());
+    }
+
+    #[test]
+    fn socket_mode_defaults_to_none() {
+        let cli = Cli::try_parse_from(["mosaic-opendal-fuse", "-p", "/mnt"]).unwrap();
+        assert_eq!(cli.socket_mode, None);
+
+        let cli =
+            Cli::try_parse_from(["mosaic-opendal-fuse", "-p", "/mnt", "--socket-mode", "640"])
+                .unwrap();
+        assert_eq!(cli.socket_mode, Some(0o640));
+
+        assert!(
+            Cli::try_parse_from(["mosaic-opendal-fuse", "-p", "/mnt", "--socket-mode", "999"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn subtype_keeps_custom_options() {
+        let mut cli =
+            Cli::try_parse_from(["mosaic-opendal-fuse", "-p", "/mnt", "--subtype", "mosaic"])
+                .unwrap();
+        assert_eq!(cli.take_subtype(), Some("mosaic".to_string()));
+        assert_eq!(cli.mount_options.custom_options, None);
+
+        let mut cli = Cli::try_parse_from([
+            "mosaic-opendal-fuse",
+            "-p",
+            "/mnt",
+            "--subtype",
+            "mosaic",
+            "--custom-options",
+            "noatime",
+        ])
+        .unwrap();
+        assert_eq!(cli.take_subtype(), None);
+        assert_eq!(
+            cli.mount_options.custom_options.as_deref(),
+            Some("noatime,subtype=mosaic")
+        );
+    }
+
+    #[test]
+    fn log_format_defaults_to_text() {
+        let cli = Cli::try_parse_from(["mosaic-opendal-fuse", "-p", "/mnt"]).unwrap();
+        assert_eq!(cli.log_format, LogFormat::Text);
+
+        let cli =
+            Cli::try_parse_from(["mosaic-opendal-fuse", "-p", "/mnt", "--log-format", "json"])
+                .unwrap();
+        assert_eq!(cli.log_format, LogFormat::Json);
+
+        assert!(
+            Cli::try_parse_from(["mosaic-opendal-fuse", "-p", "/mnt", "--log-format", "xml"])
+                .is_err()
+        );
+    }
+}
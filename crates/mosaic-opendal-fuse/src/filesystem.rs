@@ -0,0 +1,380 @@
+//! The file system served by the adapters.
+//!
+//! [`fuse3_opendal::Filesystem`] builds the attributes it reports itself. [`AttrFilesystem`]
+//! delegates every operation to it and adjusts the attributes in the replies to the
+//! configuration. It also answers `statfs` itself, so that `df` shows a sensible capacity.
+
+use std::ffi::OsStr;
+
+// The prelude gathers the request, reply and attribute types used by `PathFilesystem`.
+use fuse3::path::prelude::*;
+use fuse3_opendal::Filesystem;
+use futures::stream::{BoxStream, StreamExt};
+
+use crate::OpenDALFuseConfiguration;
+
+/// The capacity reported by `statfs` when none is configured. OpenDAL backends don't expose
+/// quotas, so this only needs to be large enough for tools not to consider the mount full.
+pub(crate) const DEFAULT_STATFS_TOTAL_BYTES: u64 = 1 << 50;
+
+/// The block size `statfs` reports capacity in when no block size is configured.
+const STATFS_BLOCK_SIZE: u32 = 4096;
+
+/// The longest file name reported by `statfs`.
+const STATFS_NAME_LEN: u32 = 255;
+
+/// Attribute overrides taken from the [`OpenDALFuseConfiguration`]. `None` keeps the value
+/// reported by [`fuse3_opendal::Filesystem`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AttrOverrides {
+    block_size: Option<u32>,
+    statfs_total_bytes: Option<u64>,
+    file_mode: Option<u32>,
+    dir_mode: Option<u32>,
+}
+
+impl AttrOverrides {
+    pub(crate) fn new(config: &OpenDALFuseConfiguration) -> Self {
+        Self {
+            block_size: config.block_size,
+            statfs_total_bytes: config.statfs_total_bytes,
+            file_mode: config.default_file_mode,
+            dir_mode: config.default_dir_mode,
+        }
+    }
+
+    /// The `statfs` reply: the configured or default capacity, all of it free, as the usage of
+    /// the backend is unknown.
+    fn statfs(&self) -> ReplyStatFs {
+        let bsize = self.block_size.unwrap_or(STATFS_BLOCK_SIZE).max(1);
+        let total = self
+            .statfs_total_bytes
+            .unwrap_or(DEFAULT_STATFS_TOTAL_BYTES);
+        let blocks = total / u64::from(bsize);
+        ReplyStatFs {
+            blocks,
+            bfree: blocks,
+            bavail: blocks,
+            files: u64::from(u32::MAX),
+            ffree: u64::from(u32::MAX),
+            bsize,
+            namelen: STATFS_NAME_LEN,
+            frsize: bsize,
+        }
+    }
+
+    fn apply(&self, attr: &mut FileAttr) {
+        if let Some(block_size) = self.block_size {
+            attr.blksize = block_size;
+        }
+        let mode = match attr.kind {
+            FileType::RegularFile => self.file_mode,
+            FileType::Directory => self.dir_mode,
+            _ => None,
+        };
+        if let Some(mode) = mode {
+            // Only the permission bits, the file type is kept in `kind`.
+            attr.perm = (mode & 0o7777) as u16;
+        }
+    }
+}
+
+/// A [`fuse3_opendal::Filesystem`] with the attributes adjusted by [`AttrOverrides`].
+pub(crate) struct AttrFilesystem {
+    inner: Filesystem,
+    overrides: AttrOverrides,
+}
+
+impl AttrFilesystem {
+    pub(crate) fn new(inner: Filesystem, overrides: AttrOverrides) -> Self {
+        Self { inner, overrides }
+    }
+
+    fn entry(&self, mut reply: ReplyEntry) -> ReplyEntry {
+        self.overrides.apply(&mut reply.attr);
+        reply
+    }
+
+    fn attr(&self, mut reply: ReplyAttr) -> ReplyAttr {
+        self.overrides.apply(&mut reply.attr);
+        reply
+    }
+}
+
+impl PathFilesystem for AttrFilesystem {
+    type DirEntryStream<'a>
+        = <Filesystem as PathFilesystem>::DirEntryStream<'a>
+    where
+        Self: 'a;
+    type DirEntryPlusStream<'a>
+        = BoxStream<'a, Result<DirectoryEntryPlus>>
+    where
+        Self: 'a;
+
+    async fn init(&self, req: Request) -> Result<ReplyInit> {
+        self.inner.init(req).await
+    }
+
+    async fn destroy(&self, req: Request) {
+        self.inner.destroy(req).await
+    }
+
+    async fn lookup(&self, req: Request, parent: &OsStr, name: &OsStr) -> Result<ReplyEntry> {
+        self.inner
+            .lookup(req, parent, name)
+            .await
+            .map(|reply| self.entry(reply))
+    }
+
+    async fn getattr(
+        &self,
+        req: Request,
+        path: Option<&OsStr>,
+        fh: Option<u64>,
+        flags: u32,
+    ) -> Result<ReplyAttr> {
+        self.inner
+            .getattr(req, path, fh, flags)
+            .await
+            .map(|reply| self.attr(reply))
+    }
+
+    async fn setattr(
+        &self,
+        req: Request,
+        path: Option<&OsStr>,
+        fh: Option<u64>,
+        set_attr: SetAttr,
+    ) -> Result<ReplyAttr> {
+        self.inner
+            .setattr(req, path, fh, set_attr)
+            .await
+            .map(|reply| self.attr(reply))
+    }
+
+    async fn symlink(
+        &self,
+        req: Request,
+        parent: &OsStr,
+        name: &OsStr,
+        link_path: &OsStr,
+    ) -> Result<ReplyEntry> {
+        self.inner
+            .symlink(req, parent, name, link_path)
+            .await
+            .map(|reply| self.entry(reply))
+    }
+
+    async fn mknod(
+        &self,
+        req: Request,
+        parent: &OsStr,
+        name: &OsStr,
+        mode: u32,
+        rdev: u32,
+    ) -> Result<ReplyEntry> {
+        self.inner
+            .mknod(req, parent, name, mode, rdev)
+            .await
+            .map(|reply| self.entry(reply))
+    }
+
+    async fn mkdir(
+        &self,
+        req: Request,
+        parent: &OsStr,
+        name: &OsStr,
+        mode: u32,
+        umask: u32,
+    ) -> Result<ReplyEntry> {
+        self.inner
+            .mkdir(req, parent, name, mode, umask)
+            .await
+            .map(|reply| self.entry(reply))
+    }
+
+    async fn unlink(&self, req: Request, parent: &OsStr, name: &OsStr) -> Result<()> {
+        self.inner.unlink(req, parent, name).await
+    }
+
+    async fn rmdir(&self, req: Request, parent: &OsStr, name: &OsStr) -> Result<()> {
+        self.inner.rmdir(req, parent, name).await
+    }
+
+    async fn rename(
+        &self,
+        req: Request,
+        origin_parent: &OsStr,
+        origin_name: &OsStr,
+        parent: &OsStr,
+        name: &OsStr,
+    ) -> Result<()> {
+        self.inner
+            .rename(req, origin_parent, origin_name, parent, name)
+            .await
+    }
+
+    async fn open(&self, req: Request, path: &OsStr, flags: u32) -> Result<ReplyOpen> {
+        self.inner.open(req, path, flags).await
+    }
+
+    async fn read(
+        &self,
+        req: Request,
+        path: Option<&OsStr>,
+        fh: u64,
+        offset: u64,
+        size: u32,
+    ) -> Result<ReplyData> {
+        self.inner.read(req, path, fh, offset, size).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn write(
+        &self,
+        req: Request,
+        path: Option<&OsStr>,
+        fh: u64,
+        offset: u64,
+        data: &[u8],
+        write_flags: u32,
+        flags: u32,
+    ) -> Result<ReplyWrite> {
+        self.inner
+            .write(req, path, fh, offset, data, write_flags, flags)
+            .await
+    }
+
+    async fn statfs(&self, _req: Request, _path: &OsStr) -> Result<ReplyStatFs> {
+        Ok(self.overrides.statfs())
+    }
+
+    async fn release(
+        &self,
+        req: Request,
+        path: Option<&OsStr>,
+        fh: u64,
+        flags: u32,
+        lock_owner: u64,
+        flush: bool,
+    ) -> Result<()> {
+        self.inner
+            .release(req, path, fh, flags, lock_owner, flush)
+            .await
+    }
+
+    async fn fsync(
+        &self,
+        req: Request,
+        path: Option<&OsStr>,
+        fh: u64,
+        datasync: bool,
+    ) -> Result<()> {
+        self.inner.fsync(req, path, fh, datasync).await
+    }
+
+    async fn flush(
+        &self,
+        req: Request,
+        path: Option<&OsStr>,
+        fh: u64,
+        lock_owner: u64,
+    ) -> Result<()> {
+        self.inner.flush(req, path, fh, lock_owner).await
+    }
+
+    async fn opendir(&self, req: Request, path: &OsStr, flags: u32) -> Result<ReplyOpen> {
+        self.inner.opendir(req, path, flags).await
+    }
+
+    async fn readdir<'a>(
+        &'a self,
+        req: Request,
+        path: &'a OsStr,
+        fh: u64,
+        offset: i64,
+    ) -> Result<ReplyDirectory<Self::DirEntryStream<'a>>> {
+        self.inner.readdir(req, path, fh, offset).await
+    }
+
+    async fn releasedir(&self, req: Request, path: &OsStr, fh: u64, flags: u32) -> Result<()> {
+        self.inner.releasedir(req, path, fh, flags).await
+    }
+
+    async fn access(&self, req: Request, path: &OsStr, mask: u32) -> Result<()> {
+        self.inner.access(req, path, mask).await
+    }
+
+    async fn create(
+        &self,
+        req: Request,
+        parent: &OsStr,
+        name: &OsStr,
+        mode: u32,
+        flags: u32,
+    ) -> Result<ReplyCreated> {
+        let mut reply = self.inner.create(req, parent, name, mode, flags).await?;
+        self.overrides.apply(&mut reply.attr);
+        Ok(reply)
+    }
+
+    async fn readdirplus<'a>(
+        &'a self,
+        req: Request,
+        parent: &'a OsStr,
+        fh: u64,
+        offset: u64,
+        lock_owner: u64,
+    ) -> Result<ReplyDirectoryPlus<Self::DirEntryPlusStream<'a>>> {
+        let reply = self
+            .inner
+            .readdirplus(req, parent, fh, offset, lock_owner)
+            .await?;
+        let overrides = self.overrides;
+        let entries = reply
+            .entries
+            .map(move |entry| {
+                entry.map(|mut entry| {
+                    overrides.apply(&mut entry.attr);
+                    entry
+                })
+            })
+            .boxed();
+        Ok(ReplyDirectoryPlus { entries })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn rename2(
+        &self,
+        req: Request,
+        origin_parent: &OsStr,
+        origin_name: &OsStr,
+        parent: &OsStr,
+        name: &OsStr,
+        flags: u32,
+    ) -> Result<()> {
+        self.inner
+            .rename2(req, origin_parent, origin_name, parent, name, flags)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn copy_file_range(
+        &self,
+        req: Request,
+        from_path: Option<&OsStr>,
+        fh_in: u64,
+        offset_in: u64,
+        to_path: Option<&OsStr>,
+        fh_out: u64,
+        offset_out: u64,
+        length: u64,
+        flags: u64,
+    ) -> Result<ReplyCopyFileRange> {
+        self.inner
+            .copy_file_range(
+                req, from_path, fh_in, offset_in, to_path, fh_out, offset_out, length, flags,
+            )
+            .await
+    }
+}
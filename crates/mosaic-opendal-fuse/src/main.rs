@@ -6,60 +6,103 @@
 //! cargo run --release mosaic-opendal-fuse --mount-path /path/to/mount
 //! ```
 
-use std::{fs, path::Path};
+use std::{fmt, fs, future::Future, os::unix::fs::PermissionsExt, path::Path, time::Duration};
 
+use arc_swap as _;
 use clap::Parser;
 use fuse3::raw::MountHandle;
 use fuse3_opendal as _;
+use futures as _;
 use opendal::{Operator, services::Memory};
+use reqwest as _;
+use serde as _;
 use thiserror as _;
 use tokio::{
     net::UnixListener,
     signal::unix::{SignalKind, signal},
     task::JoinHandle,
 };
-use tracing::{debug, error, info};
-use tracing_subscriber::EnvFilter;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::{EnvFilter, fmt::MakeWriter, util::SubscriberInitExt};
 
-use cli::Cli;
+use cli::{Cli, LogFormat};
 use mosaic_opendal_fuse::{OpenDALFuseConfiguration, S3Configuration, S3OpenDALFuseAdapter};
 
 mod cli;
 
-/// Initializes the tracing subscriber.
-fn init_tracing() {
+/// How long to wait before accepting again after a failed accept on the readiness socket, so
+/// that a socket in a bad state does not busy-loop.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Initializes the tracing subscriber, logging to stdout in the given format.
+fn init_tracing(format: LogFormat) {
+    subscriber(format, std::io::stdout).init();
+}
+
+/// Builds a subscriber logging to `writer` in the given format, filtered by `RUST_LOG` or at
+/// `info` level by default.
+fn subscriber<W>(format: LogFormat, writer: W) -> Box<dyn tracing::Subscriber + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.json().finish()),
+    }
 }
 
 /// Spawns the socket and signals tasks and returns the handles.
 async fn spawn_tasks<S: Into<String>>(
     socket_path: S,
+    socket_mode: Option<u32>,
 ) -> Result<(JoinHandle<()>, JoinHandle<()>), Box<dyn std::error::Error>> {
-    let socket = spawn_socket_listener(socket_path)?;
+    let socket = spawn_socket_listener(socket_path, socket_mode)?;
     let signals = spawn_signal_listener()?;
     Ok((socket, signals))
 }
 
-/// Spawns and returns the socket listener task.
+/// Spawns and returns the socket listener task. With a `mode`, the socket's permissions are set
+/// to it after binding.
 fn spawn_socket_listener<S: Into<String>>(
     socket_path: S,
+    mode: Option<u32>,
 ) -> Result<JoinHandle<()>, Box<dyn std::error::Error>> {
     let socket_path = socket_path.into();
     let _ = fs::remove_file(&socket_path);
 
     // Setup a socket that closes connections immediately to expose readiness.
     let listener = UnixListener::bind(&socket_path)?;
+    if let Some(mode) = mode {
+        fs::set_permissions(&socket_path, fs::Permissions::from_mode(mode))?;
+    }
     let socket = tokio::spawn(async move {
         info!("S3OpenDalFuseAdapter socket listening on {}", &socket_path);
-        loop {
-            let _ = listener.accept().await;
-        }
+        accept_loop(|| listener.accept(), ACCEPT_ERROR_BACKOFF).await;
     });
 
     Ok(socket)
 }
 
+/// Accepts connections forever, closing them immediately. Successful accepts continue right
+/// away, errors are logged and followed by a pause of `backoff`.
+async fn accept_loop<A, F, T, E>(mut accept: A, backoff: Duration)
+where
+    A: FnMut() -> F,
+    F: Future<Output = Result<T, E>>,
+    E: fmt::Display,
+{
+    loop {
+        if let Err(e) = accept().await {
+            warn!("Failed to accept socket connection: {}", e);
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
+
 /// Spawns and returns the signals listener task.
 fn spawn_signal_listener() -> Result<JoinHandle<()>, Box<dyn std::error::Error>> {
     // Setup unix signals to listen to.
@@ -88,18 +131,24 @@ async fn cleanup<P: AsRef<Path>>(mount_handle: MountHandle, socket_path: P) {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _ = dotenvy::dotenv();
-    init_tracing();
+    let mut cli = Cli::parse();
+    init_tracing(cli.log_format);
 
-    let cli = Cli::parse();
     let s3_config = S3Configuration::from_env();
     println!("{}", s3_config);
     println!("{}", cli.mount_options);
 
     let uid = cli.mount_options.uid;
     let gid = cli.mount_options.gid;
+    let fs_name = cli.mount_options.fs_name.clone();
+    let subtype = cli.take_subtype();
     let config = OpenDALFuseConfiguration {
         mount_options: cli.mount_options.into(),
+        fs_name,
+        subtype,
+        skip_fuse_conf_check: cli.skip_fuse_conf_check,
         s3: s3_config,
+        ..Default::default()
     };
 
     debug!("Starting with config: {:?}", config);
@@ -111,11 +160,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         S3OpenDALFuseAdapter::new(config)?
     };
 
-    let mut mount_handle = adapter.start_session(&cli.mount_path, uid, gid).await?;
+    let started = adapter.start_session(&cli.mount_path, uid, gid).await?;
+    info!(
+        "Mounted at {} with uid {} and gid {}",
+        started.mount_dir().display(),
+        started.uid(),
+        started.gid()
+    );
+    let mut mount_handle = started.into_inner();
     let handle = &mut mount_handle;
 
     // If some sockets fail to spawn, we need to clean up the mount point.
-    let (_socket, signals) = match spawn_tasks(cli.socket.clone()).await {
+    let (_socket, signals) = match spawn_tasks(cli.socket.clone(), cli.socket_mode).await {
         Ok(v) => v,
         Err(_) => {
             cleanup(mount_handle, cli.socket).await;
@@ -130,3 +186,98 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicU32, Ordering},
+        },
+    };
+
+    use super::*;
+
+    /// A writer collecting the log output in memory.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_logs_are_parseable() {
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = subscriber(LogFormat::Json, move || writer.clone());
+        tracing::subscriber::with_default(subscriber, || info!(mount = "/mnt", "Mounted"));
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value =
+            serde_json::from_str(output.lines().next().expect("no log line")).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "Mounted");
+        assert_eq!(line["fields"]["mount"], "/mnt");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn accept_loop_backs_off_after_errors() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counted = Arc::clone(&attempts);
+        // Two successful accepts, then failures only.
+        let task = tokio::spawn(accept_loop(
+            move || {
+                let attempt = counted.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt < 2 {
+                        Ok(())
+                    } else {
+                        Err("socket in a bad state")
+                    }
+                }
+            },
+            ACCEPT_ERROR_BACKOFF,
+        ));
+
+        tokio::task::yield_now().await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        tokio::time::advance(ACCEPT_ERROR_BACKOFF / 2).await;
+        tokio::task::yield_now().await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        tokio::time::advance(ACCEPT_ERROR_BACKOFF / 2).await;
+        tokio::task::yield_now().await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn socket_receives_requested_mode() {
+        let path = std::env::temp_dir()
+            .join(format!(
+                "mosaic_opendal_fuse_mode_{}.sock",
+                std::process::id()
+            ))
+            .to_str()
+            .unwrap()
+            .to_string();
+        let socket = spawn_socket_listener(path.clone(), Some(0o640)).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o7777, 0o640);
+
+        socket.abort();
+        fs::remove_file(&path).unwrap();
+    }
+}
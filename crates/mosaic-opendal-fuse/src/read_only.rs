@@ -0,0 +1,131 @@
+//! Read-only enforcement at the operator level.
+
+use opendal::{
+    Error, ErrorKind, Operator, Result,
+    raw::{
+        Access, Layer, LayeredAccess, OpCopy, OpCreateDir, OpList, OpRead, OpRename, OpWrite,
+        RpCopy, RpCreateDir, RpDelete, RpList, RpRead, RpRename, RpWrite,
+    },
+};
+
+/// Make an operator read-only, whatever its backend.
+///
+/// Writes, deletes, directory creation, copies and renames fail with
+/// [`ErrorKind::PermissionDenied`] before reaching the backend. Reads, stats and listings pass
+/// through unchanged.
+pub fn read_only(operator: Operator) -> Operator {
+    operator.layer(ReadOnlyLayer)
+}
+
+/// An [`opendal`] layer rejecting all mutating operations, see [`read_only`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadOnlyLayer;
+
+impl<A: Access> Layer<A> for ReadOnlyLayer {
+    type LayeredAccess = ReadOnlyAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccess {
+        ReadOnlyAccessor { inner }
+    }
+}
+
+/// The accessor created by [`ReadOnlyLayer`].
+#[derive(Debug)]
+pub struct ReadOnlyAccessor<A: Access> {
+    inner: A,
+}
+
+impl<A: Access> LayeredAccess for ReadOnlyAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type Writer = A::Writer;
+    type Lister = A::Lister;
+    type Deleter = A::Deleter;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
+        Err(denied("create_dir", path))
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        Err(denied("write", path))
+    }
+
+    async fn copy(&self, from: &str, _: &str, _: OpCopy) -> Result<RpCopy> {
+        Err(denied("copy", from))
+    }
+
+    async fn rename(&self, from: &str, _: &str, _: OpRename) -> Result<RpRename> {
+        Err(denied("rename", from))
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "delete is not allowed on a read-only operator",
+        ))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.inner.list(path, args).await
+    }
+}
+
+fn denied(operation: &'static str, path: &str) -> Error {
+    Error::new(
+        ErrorKind::PermissionDenied,
+        format!("{operation} is not allowed on a read-only operator"),
+    )
+    .with_context("path", path)
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    /// A read-only view of an in-memory operator holding `dir/file`.
+    async fn read_only_memory() -> Operator {
+        let operator = Operator::new(Memory::default()).unwrap().finish();
+        operator.write("dir/file", "content").await.unwrap();
+        read_only(operator)
+    }
+
+    fn assert_denied<T: std::fmt::Debug>(result: Result<T>) {
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn reads_pass_through() {
+        let operator = read_only_memory().await;
+
+        let content = operator.read("dir/file").await.unwrap();
+        assert_eq!(content.to_vec(), b"content");
+        assert_eq!(operator.stat("dir/file").await.unwrap().content_length(), 7);
+        let entries = operator.list("dir/").await.unwrap();
+        assert!(entries.iter().any(|entry| entry.path() == "dir/file"));
+    }
+
+    #[tokio::test]
+    async fn mutations_are_rejected() {
+        let operator = read_only_memory().await;
+
+        assert_denied(operator.write("dir/other", "content").await);
+        assert_denied(operator.delete("dir/file").await);
+        assert_denied(operator.create_dir("new/").await);
+        assert_denied(operator.rename("dir/file", "dir/renamed").await);
+
+        assert_eq!(
+            operator.read("dir/file").await.unwrap().to_vec(),
+            b"content"
+        );
+    }
+}
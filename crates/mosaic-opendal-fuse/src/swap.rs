@@ -0,0 +1,166 @@
+//! Replacing the operator behind a mounted file system.
+
+use std::{fmt, sync::Arc};
+
+use arc_swap::ArcSwap;
+use opendal::{
+    Operator, Result,
+    raw::{
+        Access, AccessorInfo, OpCopy, OpCreateDir, OpList, OpPresign, OpRead, OpRename, OpStat,
+        OpWrite, RpCopy, RpCreateDir, RpDelete, RpList, RpPresign, RpRead, RpRename, RpStat,
+        RpWrite, oio,
+    },
+};
+
+/// A shared handle to the operator a file system is served from, which can be replaced while
+/// it is mounted, e.g. to rotate credentials or reload the configuration.
+///
+/// [`OperatorSwap::operator`] returns an [`Operator`] that forwards every call to whichever
+/// operator is current at the time of the call. Clones share the same current operator.
+///
+/// ## Ordering
+///
+/// The swap is atomic: every call sees either the old or the new operator, never a mix. A call
+/// started after [`OperatorSwap::swap`] returned, on any thread, is guaranteed to see the new
+/// operator. Calls already in flight finish against the operator they started with, and so do
+/// readers, writers and listers opened before the swap.
+#[derive(Clone)]
+pub struct OperatorSwap {
+    current: Arc<ArcSwap<Operator>>,
+}
+
+impl fmt::Debug for OperatorSwap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OperatorSwap").finish_non_exhaustive()
+    }
+}
+
+impl OperatorSwap {
+    /// Returns a handle serving `operator` until it is swapped.
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(operator)),
+        }
+    }
+
+    /// Replaces the current operator, returning the previous one.
+    pub fn swap(&self, operator: Operator) -> Operator {
+        Operator::clone(&self.current.swap(Arc::new(operator)))
+    }
+
+    /// Returns the current operator.
+    pub fn current(&self) -> Operator {
+        Operator::clone(&self.current.load())
+    }
+
+    /// Returns an operator that always forwards to the current one.
+    pub fn operator(&self) -> Operator {
+        Operator::from_inner(Arc::new(SwappableAccessor {
+            current: Arc::clone(&self.current),
+        }))
+    }
+}
+
+/// The accessor of [`OperatorSwap::operator`], forwarding to the current operator.
+struct SwappableAccessor {
+    current: Arc<ArcSwap<Operator>>,
+}
+
+impl fmt::Debug for SwappableAccessor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwappableAccessor").finish_non_exhaustive()
+    }
+}
+
+impl SwappableAccessor {
+    /// The accessor of the current operator. Cloning it keeps the operator alive for the whole
+    /// call, even if it is swapped out in the meantime.
+    fn accessor(&self) -> opendal::raw::Accessor {
+        self.current.load().inner().clone()
+    }
+}
+
+impl Access for SwappableAccessor {
+    type Reader = oio::Reader;
+    type Writer = oio::Writer;
+    type Lister = oio::Lister;
+    type Deleter = oio::Deleter;
+
+    fn info(&self) -> Arc<AccessorInfo> {
+        self.accessor().info()
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.accessor().create_dir(path, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.accessor().stat(path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.accessor().read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.accessor().write(path, args).await
+    }
+
+    async fn delete(&self) -> Result<(RpDelete, Self::Deleter)> {
+        self.accessor().delete().await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Lister)> {
+        self.accessor().list(path, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.accessor().copy(from, to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.accessor().rename(from, to, args).await
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.accessor().presign(path, args).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opendal::services::Memory;
+
+    use super::*;
+
+    async fn memory_with(content: &str) -> Operator {
+        let operator = Operator::new(Memory::default()).unwrap().finish();
+        operator.write("file", content.to_owned()).await.unwrap();
+        operator
+    }
+
+    #[tokio::test]
+    async fn swap_is_observed_by_subsequent_reads() {
+        let swap = OperatorSwap::new(memory_with("old").await);
+        let operator = swap.operator();
+        assert_eq!(operator.read("file").await.unwrap().to_vec(), b"old");
+
+        let previous = swap.swap(memory_with("new").await);
+        assert_eq!(operator.read("file").await.unwrap().to_vec(), b"new");
+        assert_eq!(
+            swap.clone().operator().read("file").await.unwrap().to_vec(),
+            b"new"
+        );
+        assert_eq!(previous.read("file").await.unwrap().to_vec(), b"old");
+    }
+
+    #[tokio::test]
+    async fn writes_go_to_current_operator() {
+        let swap = OperatorSwap::new(memory_with("old").await);
+        let operator = swap.operator();
+        swap.swap(memory_with("new").await);
+
+        operator.write("other", "written").await.unwrap();
+        assert!(swap.current().exists("other").await.unwrap());
+    }
+}
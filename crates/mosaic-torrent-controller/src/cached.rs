@@ -0,0 +1,225 @@
+//! A [`TransmissionClient`] wrapper that caches torrent listings.
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+use transmission_client::Client;
+
+use mosaic_torrent_types::{BitTorrent, BitTorrentError, Peers, SessionStats, Torrent};
+
+use crate::TransmissionClient;
+use crate::logging::debug;
+use crate::ops::TransmissionOps;
+
+/// A [`TransmissionClient`] that caches the result of [`BitTorrent::list`] for a fixed TTL.
+///
+/// Every mutating [`BitTorrent`] call made through the wrapper (`add`, `stop`, `remove`, ...)
+/// invalidates the cache, whether it succeeded or not. Changes made elsewhere, including through
+/// [`CachedTransmissionClient::inner`], are only picked up once the TTL expires or after
+/// [`CachedTransmissionClient::invalidate`].
+///
+/// Clones share the same cache.
+#[allow(missing_debug_implementations, private_bounds)]
+pub struct CachedTransmissionClient<T: TransmissionOps = Client> {
+    client: TransmissionClient<T>,
+    ttl: Duration,
+    cache: Arc<Mutex<ListCache>>,
+}
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> Clone for CachedTransmissionClient<T> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            ttl: self.ttl,
+            cache: Arc::clone(&self.cache),
+        }
+    }
+}
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> CachedTransmissionClient<T> {
+    /// Wrap `client`, keeping `list` results for `ttl`.
+    pub fn new(client: TransmissionClient<T>, ttl: Duration) -> Self {
+        Self {
+            client,
+            ttl,
+            cache: Arc::new(Mutex::new(ListCache::default())),
+        }
+    }
+
+    /// Drop the cached listing, so the next `list` asks the daemon again.
+    pub fn invalidate(&self) {
+        let mut cache = self.cache();
+        cache.entry = None;
+        cache.generation += 1;
+    }
+
+    /// The wrapped client, for calls that are not cached. Mutations made through it do not
+    /// invalidate the cache.
+    pub fn inner(&self) -> &TransmissionClient<T> {
+        &self.client
+    }
+
+    fn cache(&self) -> MutexGuard<'_, ListCache> {
+        // The cache only ever holds complete values, so a poisoned lock is still usable.
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> BitTorrent for CachedTransmissionClient<T> {
+    async fn add(&self, torrent_file: &str) -> Result<Torrent, BitTorrentError> {
+        let result = self.client.add(torrent_file).await;
+        self.invalidate();
+        result
+    }
+
+    async fn stop(&self, ids: Vec<String>) -> Result<(), BitTorrentError> {
+        let result = self.client.stop(ids).await;
+        self.invalidate();
+        result
+    }
+
+    async fn list(&self) -> Result<Vec<Torrent>, BitTorrentError> {
+        let generation = {
+            let cache = self.cache();
+            let fresh = cache
+                .entry
+                .as_ref()
+                .filter(|entry| entry.fetched_at.elapsed() < self.ttl);
+            if let Some(entry) = fresh {
+                debug!("Using cached torrent list");
+                return Ok(entry.torrents.clone());
+            }
+            cache.generation
+        };
+
+        let torrents = self.client.list().await?;
+
+        // Don't store a listing that may predate a mutation made while it was being fetched.
+        let mut cache = self.cache();
+        if cache.generation == generation {
+            cache.entry = Some(CachedList {
+                fetched_at: Instant::now(),
+                torrents: torrents.clone(),
+            });
+        }
+        Ok(torrents)
+    }
+
+    async fn peers(&self, id: i32) -> Result<Peers, BitTorrentError> {
+        self.client.peers(id).await
+    }
+
+    async fn remove(
+        &self,
+        ids: Vec<String>,
+        delete_local_data: bool,
+    ) -> Result<(), BitTorrentError> {
+        let result = self.client.remove(ids, delete_local_data).await;
+        self.invalidate();
+        result
+    }
+
+    async fn stats(&self) -> Result<SessionStats, BitTorrentError> {
+        self.client.stats().await
+    }
+
+    async fn ping(&self) -> Result<(), BitTorrentError> {
+        self.client.ping().await
+    }
+
+    async fn set_honors_session_limits(
+        &self,
+        ids: Vec<String>,
+        honors: bool,
+    ) -> Result<(), BitTorrentError> {
+        let result = self.client.set_honors_session_limits(ids, honors).await;
+        self.invalidate();
+        result
+    }
+
+    async fn set_peer_limit(&self, ids: Vec<String>, limit: i32) -> Result<(), BitTorrentError> {
+        let result = self.client.set_peer_limit(ids, limit).await;
+        self.invalidate();
+        result
+    }
+}
+
+/// The cached listing, if any. `generation` is bumped on every invalidation.
+#[derive(Debug, Default)]
+struct ListCache {
+    entry: Option<CachedList>,
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct CachedList {
+    fetched_at: Instant,
+    torrents: Vec<Torrent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::MockTransmissionOps;
+    use crate::testutil::make_test_torrent;
+
+    const TTL: Duration = Duration::from_secs(5);
+
+    fn cached(mock: MockTransmissionOps) -> CachedTransmissionClient<MockTransmissionOps> {
+        CachedTransmissionClient::new(TransmissionClient::with_client(mock), TTL)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn list_is_cached_within_ttl() {
+        let mut mock = MockTransmissionOps::new();
+        mock.expect_torrents()
+            .times(1)
+            .returning(|_| Ok(vec![make_test_torrent(1, "torrent1", "hash1")]));
+
+        let client = cached(mock);
+        assert_eq!(client.list().await.unwrap().len(), 1);
+        tokio::time::advance(TTL - Duration::from_millis(1)).await;
+        assert_eq!(client.list().await.unwrap().len(), 1);
+        assert_eq!(client.clone().list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn list_is_refetched_after_ttl() {
+        let mut mock = MockTransmissionOps::new();
+        mock.expect_torrents().times(2).returning(|_| Ok(vec![]));
+
+        let client = cached(mock);
+        client.list().await.unwrap();
+        tokio::time::advance(TTL).await;
+        client.list().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn mutation_invalidates_cache() {
+        let mut mock = MockTransmissionOps::new();
+        mock.expect_torrents().times(2).returning(|_| Ok(vec![]));
+        mock.expect_torrent_stop().times(1).returning(|_| Ok(()));
+
+        let client = cached(mock);
+        client.list().await.unwrap();
+        client.stop(vec!["hash1".to_string()]).await.unwrap();
+        client.list().await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn invalidate_busts_cache() {
+        let mut mock = MockTransmissionOps::new();
+        mock.expect_torrents().times(2).returning(|_| Ok(vec![]));
+
+        let client = cached(mock);
+        client.list().await.unwrap();
+        client.invalidate();
+        client.list().await.unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+//! Per-file queries on the files of a torrent.
+
+use std::path::Path;
+
+use tokio::io::AsyncRead;
+use transmission_client::{Torrent as TransmissionTorrent, TorrentFiles, TorrentMutator};
+
+use mosaic_torrent_types::BitTorrentError;
+
+use super::TransmissionClient;
+use crate::logging::debug;
+use crate::ops::TransmissionOps;
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> TransmissionClient<T> {
+    /// Get the completion of a single file of a torrent, between `0.0` and `1.0`.
+    ///
+    /// Fails with [`BitTorrentError::InvalidTorrent`] if the torrent has no file at `file_index`.
+    pub async fn file_progress(&self, id: i32, file_index: u32) -> Result<f32, BitTorrentError> {
+        debug!("Getting progress of file {file_index} of torrent ID {id}");
+        let files = self.torrent_files(id).await?;
+        let index = file_index as usize;
+        let (file, stats) = files
+            .files
+            .get(index)
+            .zip(files.file_stats.get(index))
+            .ok_or_else(|| {
+                BitTorrentError::InvalidTorrent(format!(
+                    "No file with index {} in torrent ID {}",
+                    file_index, id
+                ))
+            })?;
+
+        let progress = if file.length == 0 {
+            1.0
+        } else {
+            stats.bytes_completed as f32 / file.length as f32
+        };
+        debug!("Progress of file {file_index} of torrent ID {id}: {progress}");
+
+        Ok(progress)
+    }
+
+    /// Open a single file of a torrent for reading, without knowing where the daemon stores it.
+    ///
+    /// The path is resolved from the torrent's download directory and file list, so the daemon
+    /// must run on this host or share its file system with it. Fails with
+    /// [`BitTorrentError::ServerError`] if the file is not fully downloaded yet.
+    pub async fn open_file(
+        &self,
+        id: i32,
+        file_index: u32,
+    ) -> Result<impl AsyncRead + use<T>, BitTorrentError> {
+        debug!("Opening file {file_index} of torrent ID {id}");
+        let files = self.torrent_files(id).await?;
+        let file = files.files.get(file_index as usize).ok_or_else(|| {
+            BitTorrentError::InvalidTorrent(format!(
+                "No file with index {} in torrent ID {}",
+                file_index, id
+            ))
+        })?;
+        if file.bytes_completed < file.length {
+            return Err(BitTorrentError::ServerError(format!(
+                "file {} of torrent ID {} is not fully downloaded ({} of {} bytes)",
+                file_index, id, file.bytes_completed, file.length
+            )));
+        }
+
+        let download_dir = self.torrent_by_id(id).await?.download_dir;
+        let path = Path::new(&download_dir).join(&file.name);
+        debug!("Opening {}", path.display());
+
+        tokio::fs::File::open(&path).await.map_err(|e| {
+            BitTorrentError::FileSystem(format!("failed to open {}: {}", path.display(), e))
+        })
+    }
+
+    /// Select which files of a torrent are downloaded, by their index in the file list. Files in
+    /// neither list keep their current setting.
+    pub async fn set_files_wanted(
+        &self,
+        id: i32,
+        wanted: Vec<u32>,
+        unwanted: Vec<u32>,
+    ) -> Result<(), BitTorrentError> {
+        debug!("Setting wanted files of torrent ID {id}: wanted={wanted:?} unwanted={unwanted:?}");
+        let hash = self.torrent_by_id(id).await?.hash_string;
+        let mutator = TorrentMutator {
+            files_wanted: file_indices(wanted)?,
+            files_unwanted: file_indices(unwanted)?,
+            ..Default::default()
+        };
+        self.call(|ops| ops.torrent_set(Some(vec![hash]), mutator))
+            .await?;
+        debug!("Wanted files set");
+        Ok(())
+    }
+
+    /// Select which files of a torrent are downloaded, by path prefix.
+    ///
+    /// Prefixes are matched against the file paths as listed by the daemon, which include the
+    /// torrent's top-level folder, e.g. `folder/samples/` or `folder/movie.mkv`. A prefix only
+    /// matches whole path components. When a file matches prefixes in both lists, the longest
+    /// prefix wins, so a single file can be kept out of an unwanted folder. Files matching no
+    /// prefix keep their current setting.
+    ///
+    /// Fails with [`BitTorrentError::InvalidTorrent`] if a prefix matches no file, as that is most
+    /// likely a typo.
+    pub async fn set_files_wanted_by_path(
+        &self,
+        id: i32,
+        wanted_prefixes: Vec<String>,
+        unwanted_prefixes: Vec<String>,
+    ) -> Result<(), BitTorrentError> {
+        let files = self.torrent_files(id).await?;
+        let prefixes = wanted_prefixes
+            .iter()
+            .map(|prefix| (prefix, true))
+            .chain(unwanted_prefixes.iter().map(|prefix| (prefix, false)));
+
+        let mut matches = vec![None; files.files.len()];
+        for (prefix, wanted) in prefixes {
+            let mut matched = false;
+            for (index, file) in files.files.iter().enumerate() {
+                if !path_has_prefix(&file.name, prefix) {
+                    continue;
+                }
+                matched = true;
+                // On equal length the unwanted prefix, which comes later, wins.
+                if matches[index].is_none_or(|(len, _)| prefix.len() >= len) {
+                    matches[index] = Some((prefix.len(), wanted));
+                }
+            }
+            if !matched {
+                return Err(BitTorrentError::InvalidTorrent(format!(
+                    "No file in torrent ID {} matches `{}`",
+                    id, prefix
+                )));
+            }
+        }
+
+        let (mut wanted, mut unwanted) = (Vec::new(), Vec::new());
+        for (index, selection) in matches.into_iter().enumerate() {
+            match selection {
+                Some((_, true)) => wanted.push(index as u32),
+                Some((_, false)) => unwanted.push(index as u32),
+                None => {}
+            }
+        }
+        self.set_files_wanted(id, wanted, unwanted).await
+    }
+
+    /// Fetch a single torrent by its id.
+    pub(super) async fn torrent_by_id(
+        &self,
+        id: i32,
+    ) -> Result<TransmissionTorrent, BitTorrentError> {
+        self.call(|ops| ops.torrents(Some(vec![id])))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                BitTorrentError::InvalidTorrent(format!("No torrent found with ID {}", id))
+            })
+    }
+
+    /// Fetch the file list of a single torrent.
+    async fn torrent_files(&self, id: i32) -> Result<TorrentFiles, BitTorrentError> {
+        self.call(|ops| ops.torrents_files(Some(vec![id])))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                BitTorrentError::InvalidTorrent(format!("No files found for torrent ID {}", id))
+            })
+    }
+}
+
+/// Converts file indices for the RPC. An empty list becomes `None`, as Transmission treats an
+/// empty `files-wanted` or `files-unwanted` as all files.
+fn file_indices(indices: Vec<u32>) -> Result<Option<Vec<i32>>, BitTorrentError> {
+    if indices.is_empty() {
+        return Ok(None);
+    }
+    indices
+        .into_iter()
+        .map(|index| {
+            i32::try_from(index)
+                .map_err(|_| BitTorrentError::InvalidTorrent(format!("invalid file index {index}")))
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Whether `path` equals `prefix` or lies below it, comparing whole path components.
+fn path_has_prefix(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/') || prefix.is_empty(),
+        None => false,
+    }
+}
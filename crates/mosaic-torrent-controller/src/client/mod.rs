@@ -1,23 +1,115 @@
 //! Transmission RPC client implementation.
 
-use tracing::debug;
-use transmission_client::{Client, ClientError, SessionMutator};
-use url::Url;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::Permissions,
+    os::unix::fs::PermissionsExt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use futures::{StreamExt, TryStreamExt, stream};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use transmission_client::{Client, ClientError, SessionMutator, TorrentMutator, TorrentPeers};
+use url::{Host, Url};
 
-use mosaic_torrent_types::{BitTorrent, BitTorrentError, Peers, SessionStats, Torrent};
+use mosaic_torrent_types::{
+    AddOptions, AddedTorrent, BitTorrent, BitTorrentError, PeerTotals, Peers, SessionStats,
+    Torrent, TorrentKey, VerifyMode, create_torrent_file_async, torrent_file_size,
+};
 
 use crate::conversions::{
     TransmissionSessionStatsWrapper, TransmissionTorrentPeersWrapper, TransmissionTorrentWrapper,
 };
+use crate::logging::debug;
 use crate::ops::TransmissionOps;
+use crate::poll::PollStrategy;
+use session::SessionDirs;
+
+/// The minimum supported Transmission RPC version. The torrent field `editDate`, read into
+/// [`Torrent::edit_date`], is only reported from RPC version 16 (Transmission 3.00) onwards.
+/// Every other field and method used dates from earlier versions.
+const MIN_RPC_VERSION: i32 = 16;
+
+/// The default maximum number of torrent ids sent in a single `stop` or `remove` request.
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// How to poll while waiting for the metadata of a torrent. Metadata usually resolves within
+/// seconds once peers are found, so start quickly and back off for slow swarms.
+const METADATA_POLL_STRATEGY: PollStrategy = PollStrategy::Exponential {
+    base: Duration::from_millis(250),
+    max: Duration::from_secs(2),
+};
 
+/// The RPC result Transmission replies with for methods it does not know.
+const UNRECOGNIZED_METHOD: &str = "method name not recognized";
+
+/// How long to wait between connection attempts while waiting for the daemon to become ready.
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+mod files;
+mod session;
 #[cfg(test)]
 mod tests;
+mod torrent;
 
 /// TransmissionClient is a BitTorrent client that uses Transmission RPC.
+///
+/// Cloning is cheap, as all clones share the same underlying RPC client. The client is
+/// `Send + Sync` whenever the underlying client is, which holds for the default [`Client`],
+/// so a single client can be cloned into as many tasks as needed.
+///
+/// Large `stop` and `remove` id lists are sent in batches, see
+/// [`TransmissionClient::with_batch_size`].
+///
+/// Prefer [`TransmissionClient::remove_keep_data`] and [`TransmissionClient::remove_with_data`]
+/// over [`BitTorrent::remove`], whose `delete_local_data` flag is easy to get wrong. See
+/// [`TransmissionClient::with_delete_confirmation`] to refuse data deletion through the latter.
 #[allow(missing_debug_implementations, private_bounds)]
 pub struct TransmissionClient<T: TransmissionOps = Client> {
-    client: T,
+    client: Arc<T>,
+    /// The daemon's directories, cached after the first lookup.
+    session_dirs: Arc<Mutex<SessionDirs>>,
+    /// The maximum number of torrent ids sent in a single `stop` or `remove` request.
+    batch_size: usize,
+    /// Whether the daemon runs on this host, so its paths are local paths.
+    daemon_is_local: bool,
+    /// Whether deleting local data requires [`TransmissionClient::remove_with_data`].
+    require_delete_confirmation: bool,
+    /// The number of RPC calls in flight across all clones, see [`TransmissionClient::rpc`].
+    outstanding: Arc<AtomicUsize>,
+    /// How long to wait for the response to each RPC call, unbounded if `None`.
+    request_timeout: Option<Duration>,
+}
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> Clone for TransmissionClient<T> {
+    fn clone(&self) -> Self {
+        Self {
+            client: Arc::clone(&self.client),
+            session_dirs: Arc::clone(&self.session_dirs),
+            batch_size: self.batch_size,
+            daemon_is_local: self.daemon_is_local,
+            require_delete_confirmation: self.require_delete_confirmation,
+            outstanding: Arc::clone(&self.outstanding),
+            request_timeout: self.request_timeout,
+        }
+    }
+}
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> Drop for TransmissionClient<T> {
+    fn drop(&mut self) {
+        // Only other clones can still have calls in flight, as every call borrows its client.
+        let outstanding = self.outstanding.load(Ordering::Relaxed);
+        if outstanding > 0 {
+            debug!("TransmissionClient dropped with {outstanding} RPC calls outstanding");
+        }
+    }
 }
 
 impl TransmissionClient {
@@ -25,35 +117,714 @@ impl TransmissionClient {
     ///
     /// This method is async as the session settings are applied on creation.
     pub async fn try_new(rpc_url: &str, max_downloads: u32) -> Result<Self, BitTorrentError> {
+        Self::try_new_with_timeouts(rpc_url, max_downloads, None, None).await
+    }
+
+    /// Create a new TransmissionClient like [`TransmissionClient::try_new`], bounding how long
+    /// the daemon may take to answer.
+    ///
+    /// `connect_timeout` bounds the first request, which checks the daemon version; if the
+    /// daemon does not answer in time it is considered unreachable and this fails with
+    /// [`BitTorrentError::Network`]. `request_timeout` bounds every later request, including
+    /// applying the session settings, see [`TransmissionClient::with_request_timeout`].
+    ///
+    /// Neither timeout is set on the HTTP client, as the Transmission client builds its own.
+    /// They bound the whole request instead, so a connect timeout also covers a daemon that
+    /// accepts the connection but is slow to answer the first request.
+    pub async fn try_new_with_timeouts(
+        rpc_url: &str,
+        max_downloads: u32,
+        connect_timeout: Option<Duration>,
+        request_timeout: Option<Duration>,
+    ) -> Result<Self, BitTorrentError> {
         let url = Url::parse(rpc_url)
             .map_err(|e| BitTorrentError::Other(format!("Invalid RPC URL: {}", e)))?;
 
         debug!("Connecting to Transmission RPC at {}", url);
-        let client = Client::new(url);
-        let session_mutator = SessionMutator {
-            incomplete_dir_enabled: Some(true),
-            download_queue_enabled: Some(true),
-            download_queue_size: Some(max_downloads as i32),
-            ..Default::default()
-        };
+        let daemon_is_local = is_local(&url);
+        let mut client = Self::try_with_client_timeouts(
+            Client::new(url),
+            max_downloads,
+            connect_timeout,
+            request_timeout,
+        )
+        .await?;
+        client.daemon_is_local = daemon_is_local;
+        Ok(client)
+    }
 
-        client
-            .session_set(session_mutator)
-            .await
-            .map_err(map_client_error)?;
+    /// Create a new TransmissionClient like [`TransmissionClient::try_new`], but wait up to
+    /// `wait_timeout` for the daemon to become reachable, e.g. while it is still booting.
+    ///
+    /// Only network errors are retried, other failures such as [`BitTorrentError::Unauthorized`]
+    /// are returned immediately. Fails with [`BitTorrentError::Timeout`] if the daemon is still
+    /// unreachable once `wait_timeout` has elapsed.
+    pub async fn try_new_waiting(
+        rpc_url: &str,
+        max_downloads: u32,
+        wait_timeout: Duration,
+    ) -> Result<Self, BitTorrentError> {
+        let url = Url::parse(rpc_url)
+            .map_err(|e| BitTorrentError::Other(format!("Invalid RPC URL: {}", e)))?;
 
-        debug!("Connected to Transmission Daemon");
-        Ok(Self { client })
+        debug!(
+            "Connecting to Transmission RPC at {}, waiting up to {wait_timeout:?}",
+            url
+        );
+        let daemon_is_local = is_local(&url);
+        let mut client =
+            Self::try_with_client_waiting(Client::new(url), max_downloads, wait_timeout).await?;
+        client.daemon_is_local = daemon_is_local;
+        Ok(client)
     }
 }
 
 #[allow(private_bounds)]
 impl<T: TransmissionOps> TransmissionClient<T> {
+    /// Create a TransmissionClient around the given client implementation, checking the daemon
+    /// version and applying the session settings.
+    pub(crate) async fn try_with_client(
+        client: T,
+        max_downloads: u32,
+    ) -> Result<Self, BitTorrentError> {
+        Self::try_with_client_timeouts(client, max_downloads, None, None).await
+    }
+
+    /// Like [`TransmissionClient::try_with_client`], with the timeouts of
+    /// [`TransmissionClient::try_new_with_timeouts`].
+    pub(crate) async fn try_with_client_timeouts(
+        client: T,
+        max_downloads: u32,
+        connect_timeout: Option<Duration>,
+        request_timeout: Option<Duration>,
+    ) -> Result<Self, BitTorrentError> {
+        let mut client = Self::from_ops(client);
+        client.request_timeout = request_timeout;
+        client
+            .configure_session(max_downloads, connect_timeout)
+            .await?;
+        Ok(client)
+    }
+
+    /// Like [`TransmissionClient::try_with_client`], but retries while the daemon is unreachable
+    /// until `wait_timeout` elapses. An attempt still running at that point is abandoned, so a
+    /// daemon that accepts connections but does not answer cannot block past `wait_timeout`.
+    pub(crate) async fn try_with_client_waiting(
+        client: T,
+        max_downloads: u32,
+        wait_timeout: Duration,
+    ) -> Result<Self, BitTorrentError> {
+        let client = Self::from_ops(client);
+        let deadline = Instant::now() + wait_timeout;
+        loop {
+            let attempt =
+                tokio::time::timeout_at(deadline, client.configure_session(max_downloads, None))
+                    .await;
+            let msg = match attempt {
+                Ok(Ok(())) => return Ok(client),
+                Ok(Err(BitTorrentError::Network(msg))) => msg,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => "no response from the daemon".to_string(),
+            };
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(BitTorrentError::Timeout(format!(
+                    "daemon not ready after {wait_timeout:?}: {msg}"
+                )));
+            }
+            debug!("Transmission daemon not ready yet: {msg}");
+            tokio::time::sleep(READY_POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+
     /// Create a TransmissionClient with a custom client implementation.
     /// This is primarily useful for testing with mocks.
     #[cfg(test)]
     pub(crate) fn with_client(client: T) -> Self {
-        Self { client }
+        Self::from_ops(client)
+    }
+
+    /// Wrap a client implementation without touching the daemon.
+    fn from_ops(client: T) -> Self {
+        Self {
+            client: Arc::new(client),
+            session_dirs: Arc::default(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            daemon_is_local: false,
+            require_delete_confirmation: false,
+            outstanding: Arc::default(),
+            request_timeout: None,
+        }
+    }
+
+    /// Checks the daemon version and applies the session settings.
+    ///
+    /// The version check is the first request to the daemon, so it is bounded by
+    /// `connect_timeout` rather than the request timeout, failing with
+    /// [`BitTorrentError::Network`]. Applying the settings is an ordinary call.
+    async fn configure_session(
+        &self,
+        max_downloads: u32,
+        connect_timeout: Option<Duration>,
+    ) -> Result<(), BitTorrentError> {
+        let session = {
+            let _in_flight = self.rpc();
+            let session = self.client.session();
+            match connect_timeout {
+                Some(limit) => tokio::time::timeout(limit, session).await.map_err(|_| {
+                    BitTorrentError::Network(format!("daemon did not answer in {limit:?}"))
+                })?,
+                None => session.await,
+            }
+        }
+        .map_err(map_client_error)?;
+        if session.rpc_version < MIN_RPC_VERSION {
+            debug!(
+                "Transmission {} uses unsupported RPC version {}",
+                session.version, session.rpc_version
+            );
+            return Err(BitTorrentError::UnsupportedVersion {
+                found: session.rpc_version.to_string(),
+                minimum: MIN_RPC_VERSION.to_string(),
+            });
+        }
+
+        let session_mutator = SessionMutator {
+            incomplete_dir_enabled: Some(true),
+            download_queue_enabled: Some(true),
+            // The RPC field is an i32, saturate rather than wrap to a negative queue size.
+            download_queue_size: Some(i32::try_from(max_downloads).unwrap_or(i32::MAX)),
+            ..Default::default()
+        };
+        self.call(|ops| ops.session_set(session_mutator)).await?;
+
+        debug!("Connected to Transmission Daemon {}", session.version);
+        Ok(())
+    }
+
+    /// Count an outstanding RPC call until the returned guard is dropped.
+    fn rpc(&self) -> InFlight<'_> {
+        self.outstanding.fetch_add(1, Ordering::Relaxed);
+        InFlight {
+            outstanding: &self.outstanding,
+        }
+    }
+
+    /// Run the RPC call made by `op`, counting it as outstanding and failing with
+    /// [`BitTorrentError::Timeout`] if it does not complete within the request timeout.
+    async fn call<'a, R, F>(&'a self, op: impl FnOnce(&'a T) -> F) -> Result<R, BitTorrentError>
+    where
+        F: Future<Output = Result<R, ClientError>>,
+    {
+        let _in_flight = self.rpc();
+        let call = op(&self.client);
+        let result = match self.request_timeout {
+            Some(limit) => tokio::time::timeout(limit, call).await.map_err(|_| {
+                BitTorrentError::Timeout(format!("no response from the daemon in {limit:?}"))
+            })?,
+            None => call.await,
+        };
+        result.map_err(map_client_error)
+    }
+
+    /// Fail RPC calls with [`BitTorrentError::Timeout`] if the daemon does not respond within
+    /// `timeout`, e.g. to give up on a stalled `list`. Unbounded by default.
+    ///
+    /// The timeout applies to each request on its own, so a `stop` or `remove` sent in several
+    /// batches may take longer in total. It is not set on the HTTP client, as the Transmission
+    /// client builds its own, but bounds the whole request from sending it to reading the
+    /// response.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum number of torrent ids sent in a single `stop` or `remove` request,
+    /// 500 by default. Larger id lists are split into batches sent one after the other, as some
+    /// setups limit the request size. A size of `0` is treated as `1`.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Refuse to delete local data through [`BitTorrent::remove`], so that it can only be deleted
+    /// by the explicitly named [`TransmissionClient::remove_with_data`]. With the guard enabled,
+    /// `remove` with `delete_local_data` set fails with [`BitTorrentError::Other`] without
+    /// removing anything. Disabled by default.
+    pub fn with_delete_confirmation(mut self, required: bool) -> Self {
+        self.require_delete_confirmation = required;
+        self
+    }
+
+    /// Remove the given torrents (by hash) from the client, keeping their downloaded data.
+    pub async fn remove_keep_data(&self, ids: Vec<String>) -> Result<(), BitTorrentError> {
+        self.remove_torrents(ids, false).await
+    }
+
+    /// Remove the given torrents (by hash) from the client and delete their downloaded data.
+    /// This cannot be undone.
+    pub async fn remove_with_data(&self, ids: Vec<String>) -> Result<(), BitTorrentError> {
+        self.remove_torrents(ids, true).await
+    }
+
+    /// Add a torrent file like [`BitTorrent::add`], additionally reporting whether the metadata
+    /// is complete and whether the torrent was already present.
+    ///
+    /// Duplicates are detected by listing the torrents before adding, so a concurrent add of the
+    /// same torrent by another client may go unnoticed. The add reply only holds the id, name and
+    /// hash of the torrent, so the torrent is fetched again for the rest.
+    pub async fn add_detailed(&self, torrent_file: &str) -> Result<AddedTorrent, BitTorrentError> {
+        debug!("Adding torrent with details from file: {}", torrent_file);
+        let existing: HashSet<String> = self
+            .call(|ops| ops.torrents(None))
+            .await?
+            .into_iter()
+            .map(|t| t.hash_string)
+            .collect();
+        let added = self
+            .call(|ops| ops.torrent_add_filename(torrent_file))
+            .await?
+            .ok_or_else(|| BitTorrentError::InvalidTorrent("No torrent returned".into()))?;
+        let torrent = self.torrent_by_id(added.id).await?;
+
+        let duplicate = existing.contains(&torrent.hash_string);
+        let metadata_complete = torrent.metadata_percent_complete >= 1.0;
+        debug!("Added {torrent:?}, duplicate={duplicate}, metadata_complete={metadata_complete}");
+        Ok(AddedTorrent {
+            torrent: TransmissionTorrentWrapper(torrent).into(),
+            metadata_complete,
+            duplicate,
+        })
+    }
+
+    /// Add a torrent file like [`BitTorrent::add`], then verify its local data as set by
+    /// `options.verify_on_add`.
+    ///
+    /// With [`VerifyMode::Always`], a verification is requested once the add succeeds; it runs
+    /// in the background on the daemon. The Transmission RPC has no way to skip the check of
+    /// existing data on add, so [`VerifyMode::Never`] only refrains from requesting one, the same
+    /// as [`VerifyMode::Auto`].
+    pub async fn add_with_options(
+        &self,
+        torrent_file: &str,
+        options: &AddOptions,
+    ) -> Result<Torrent, BitTorrentError> {
+        let torrent = self.add(torrent_file).await?;
+        if options.verify_on_add == VerifyMode::Always {
+            debug!("Verifying added torrent {}", torrent.hash_string);
+            self.call(|ops| ops.torrent_verify(Some(vec![torrent.hash_string.clone()])))
+                .await?;
+            debug!("Verify command sent");
+        }
+        Ok(torrent)
+    }
+
+    /// Add a torrent file like [`BitTorrent::add`], but only if the daemon has enough free space
+    /// for the torrent's content. Incomplete downloads are written to the incomplete directory
+    /// and moved to the download directory once complete, so both need the space when they
+    /// differ.
+    ///
+    /// Fails with [`BitTorrentError::InsufficientSpace`] without adding the torrent otherwise,
+    /// reporting the space available in the first directory that is too small.
+    pub async fn add_if_space(&self, torrent_file: &str) -> Result<Torrent, BitTorrentError> {
+        let required = torrent_file_size(torrent_file)?;
+        let download_dir = self.download_dir().await?;
+        let incomplete_dir = self
+            .incomplete_dir()
+            .await?
+            .filter(|dir| *dir != download_dir);
+
+        for dir in incomplete_dir.into_iter().chain([download_dir]) {
+            let available = self.free_space(&dir).await?;
+            if available < required {
+                debug!(
+                    "Not adding {torrent_file}: {required} bytes required, {available} available \
+                     in {dir}"
+                );
+                return Err(BitTorrentError::InsufficientSpace {
+                    required,
+                    available,
+                });
+            }
+        }
+
+        self.add(torrent_file).await
+    }
+
+    /// Create a torrent of `folder` and add it, without keeping the torrent file around.
+    ///
+    /// The torrent is written to a temporary file, which is removed once the add completes,
+    /// whether it succeeded or not. The daemon reads the file by path, so it must run on this
+    /// host and share its temporary directory. The file is made world-readable, as the daemon
+    /// often runs as another user.
+    pub async fn add_from_folder(
+        &self,
+        folder: &str,
+        tracker: Option<&str>,
+    ) -> Result<Torrent, BitTorrentError> {
+        let temp_file = tempfile::Builder::new()
+            .prefix("mosaic-")
+            .suffix(".torrent")
+            .permissions(Permissions::from_mode(0o644))
+            .tempfile()?;
+        let torrent_file = temp_file.path().to_str().ok_or_else(|| {
+            BitTorrentError::FileSystem(format!(
+                "temporary path is not valid UTF-8: {}",
+                temp_file.path().display()
+            ))
+        })?;
+
+        debug!("Creating torrent of {folder} at {torrent_file}");
+        create_torrent_file_async(folder, torrent_file, tracker.map(str::to_owned)).await?;
+        self.add(torrent_file).await
+    }
+
+    /// Poll the torrent with the given hash until it is fully downloaded, waiting between polls
+    /// as set by `poll`. A plain [`Duration`] polls at a fixed interval.
+    ///
+    /// Fails with [`BitTorrentError::Timeout`] if the torrent is not complete within `timeout`,
+    /// and with [`BitTorrentError::Cancelled`] as soon as `cancel` is triggered, e.g. when the
+    /// calling service shuts down.
+    pub async fn wait_until_complete(
+        &self,
+        hash: &str,
+        poll: impl Into<PollStrategy>,
+        timeout: Duration,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Torrent, BitTorrentError> {
+        self.wait_for(
+            hash,
+            poll.into(),
+            timeout,
+            cancel,
+            "complete",
+            Torrent::is_complete,
+        )
+        .await
+    }
+
+    /// Poll the torrent with the given hash until its metadata is known, e.g. after adding a
+    /// magnet link, so that its files can be queried.
+    ///
+    /// Fails with [`BitTorrentError::Timeout`] if the metadata is not complete within `timeout`.
+    pub async fn wait_for_metadata(
+        &self,
+        hash: &str,
+        timeout: Duration,
+    ) -> Result<Torrent, BitTorrentError> {
+        self.wait_for(
+            hash,
+            METADATA_POLL_STRATEGY,
+            timeout,
+            None,
+            "resolve its metadata",
+            |torrent| torrent.metadata_percent_complete >= 1.0,
+        )
+        .await
+    }
+
+    /// Poll the torrent with the given hash until `done`, see
+    /// [`TransmissionClient::wait_until_complete`]. `goal` describes `done` in log and error
+    /// messages.
+    async fn wait_for(
+        &self,
+        hash: &str,
+        strategy: PollStrategy,
+        timeout: Duration,
+        cancel: Option<&CancellationToken>,
+        goal: &str,
+        done: impl Fn(&Torrent) -> bool,
+    ) -> Result<Torrent, BitTorrentError> {
+        debug!("Waiting for torrent {hash} to {goal}, polling {strategy:?}");
+        let poll = async {
+            // Only the first lookup needs the full list, later polls fetch the torrent by id.
+            let mut torrent = self.torrent_by_hash(hash).await?;
+            let mut attempt = 0u32;
+            while !done(&torrent) {
+                tokio::time::sleep(strategy.interval(attempt)).await;
+                attempt = attempt.saturating_add(1);
+                torrent = TransmissionTorrentWrapper(self.torrent_by_id(torrent.id).await?).into();
+            }
+            Ok(torrent)
+        };
+        let cancelled = async {
+            match cancel {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            result = tokio::time::timeout(timeout, poll) => result.map_err(|_| {
+                BitTorrentError::Timeout(format!("torrent {hash} did not {goal} in {timeout:?}"))
+            })?,
+            _ = cancelled => {
+                debug!("Waiting for torrent {hash} cancelled");
+                Err(BitTorrentError::Cancelled)
+            }
+        }
+    }
+
+    /// List the torrents added strictly before `cutoff`. Torrents without a known added date
+    /// are never included.
+    pub async fn added_before(&self, cutoff: SystemTime) -> Result<Vec<Torrent>, BitTorrentError> {
+        self.added_matching(|added| added < cutoff).await
+    }
+
+    /// List the torrents added strictly after `cutoff`. Torrents without a known added date are
+    /// never included.
+    pub async fn added_after(&self, cutoff: SystemTime) -> Result<Vec<Torrent>, BitTorrentError> {
+        self.added_matching(|added| added > cutoff).await
+    }
+
+    /// List the torrents that changed on the daemon since `since`, for incremental syncs.
+    ///
+    /// A torrent counts as changed when it was added, had activity (data sent or received) or
+    /// had its settings edited, see [`Torrent::changed_at`]. Transmission records these dates in
+    /// whole seconds, so torrents changed within the same second as `since` are included too:
+    /// polling with the time of the previous poll may report a torrent twice, but never misses
+    /// one.
+    pub async fn changed_since(&self, since: SystemTime) -> Result<Vec<Torrent>, BitTorrentError> {
+        let since = since
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(since, |d| {
+                SystemTime::UNIX_EPOCH + Duration::from_secs(d.as_secs())
+            });
+        let torrents = self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|t| t.changed_at().is_some_and(|changed| changed >= since))
+            .collect();
+        Ok(torrents)
+    }
+
+    /// Find the torrent with the given info hash, whatever its name, e.g. to check whether a
+    /// locally created torrent is already present before adding it. The hash is compared
+    /// case-insensitively.
+    pub async fn find_by_info_hash(
+        &self,
+        info_hash: &str,
+    ) -> Result<Option<Torrent>, BitTorrentError> {
+        debug!("Looking up torrent with info hash {info_hash}");
+        let key = TorrentKey::new(info_hash);
+        let torrent = self.list().await?.into_iter().find(|t| t.key() == key);
+        debug!(
+            "Torrent with info hash {info_hash} found: {}",
+            torrent.is_some()
+        );
+        Ok(torrent)
+    }
+
+    /// Get the torrents with the given info hashes, in the order of `hashes`, in a single
+    /// request. Hashes are compared case-insensitively, and hashes without a torrent are left
+    /// out of the result.
+    ///
+    /// The RPC client only accepts numeric ids, so all torrents are fetched and filtered here.
+    pub async fn get_many(&self, hashes: Vec<String>) -> Result<Vec<Torrent>, BitTorrentError> {
+        debug!("Getting torrents {hashes:?}");
+        let mut by_key: HashMap<TorrentKey, Torrent> = self
+            .list()
+            .await?
+            .into_iter()
+            .map(|t| (t.key(), t))
+            .collect();
+        let torrents: Vec<Torrent> = hashes
+            .iter()
+            .filter_map(|hash| by_key.remove(&TorrentKey::new(hash)))
+            .collect();
+        debug!("Found {} of {} torrents", torrents.len(), hashes.len());
+
+        Ok(torrents)
+    }
+
+    /// List the torrents whose added date is known and matches `filter`.
+    async fn added_matching(
+        &self,
+        filter: impl Fn(SystemTime) -> bool,
+    ) -> Result<Vec<Torrent>, BitTorrentError> {
+        let torrents = self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|t| t.added_at().is_some_and(&filter))
+            .collect();
+        Ok(torrents)
+    }
+
+    /// Get the peer counts summed across all torrents, fetched in a single request.
+    pub async fn total_peers(&self) -> Result<PeerTotals, BitTorrentError> {
+        debug!("Getting peer totals");
+        let totals = self
+            .call(|ops| ops.torrents_peers(None))
+            .await?
+            .iter()
+            .fold(PeerTotals::default(), |totals, peers| PeerTotals {
+                connected: totals.connected + peers.peers_connected,
+                sending_to_us: totals.sending_to_us + peers.peers_sending_to_us,
+                getting_from_us: totals.getting_from_us + peers.peers_getting_from_us,
+            });
+        debug!("Peer totals: {totals:?}");
+
+        Ok(totals)
+    }
+
+    /// Get the peers of many torrents, fetching at most `concurrency` of them at a time so as not
+    /// to overwhelm the daemon. A concurrency of `0` is treated as `1`.
+    ///
+    /// The results are in the order of `ids`. Fails with the first error encountered.
+    pub async fn peers_many(
+        &self,
+        ids: Vec<i32>,
+        concurrency: usize,
+    ) -> Result<Vec<Peers>, BitTorrentError> {
+        debug!(
+            "Getting peers for {} torrents, {concurrency} at a time",
+            ids.len()
+        );
+        // The peers replies carry no hash, so look up the hashes of all torrents at once.
+        let hashes = self.hashes_by_id(ids.clone()).await?;
+        let mut peers: Vec<(usize, i32, TorrentPeers)> = stream::iter(ids.into_iter().enumerate())
+            .map(|(index, id)| async move { Ok((index, id, self.torrent_peers(id).await?)) })
+            .buffer_unordered(concurrency.max(1))
+            .try_collect()
+            .await?;
+        peers.sort_unstable_by_key(|&(index, _, _)| index);
+
+        peers
+            .into_iter()
+            .map(|(_, id, peers)| {
+                let hash = Self::hash_of(&hashes, id)?;
+                Ok(TransmissionTorrentPeersWrapper(peers, hash).into())
+            })
+            .collect()
+    }
+
+    /// Get the directory the torrent's data currently lives in: the daemon's incomplete
+    /// directory while it is downloading, if enabled, and its download directory once complete.
+    /// See [`Torrent::active_dir`].
+    pub async fn active_dir(&self, torrent: &Torrent) -> Result<String, BitTorrentError> {
+        let incomplete_dir = self.incomplete_dir().await?;
+        Ok(torrent.active_dir(incomplete_dir.as_deref()).to_owned())
+    }
+
+    /// Move the data of a finished torrent to the session's download directory, e.g. when it is
+    /// still in the incomplete directory.
+    ///
+    /// Fails with [`BitTorrentError::ServerError`] if the torrent has not finished downloading.
+    pub async fn move_to_complete(&self, id: &str) -> Result<(), BitTorrentError> {
+        let torrent = self.torrent_by_hash(id).await?;
+        if !torrent.is_complete() {
+            return Err(BitTorrentError::ServerError(format!(
+                "torrent {id} is not finished ({:.1}% done)",
+                torrent.percent_done * 100.0
+            )));
+        }
+
+        let download_dir = self.download_dir().await?;
+        debug!("Moving torrent {id} to {download_dir}");
+        self.call(|ops| ops.torrent_set_location(Some(vec![id.to_owned()]), download_dir, true))
+            .await?;
+        debug!("Move command sent");
+        Ok(())
+    }
+
+    /// Run `op` on the ids in batches of at most `batch_size`, one batch after the other.
+    ///
+    /// All batches are attempted even if some fail. A single failure is returned as is, several
+    /// are combined into one [`BitTorrentError::ServerError`].
+    async fn in_batches<F, Fut>(&self, ids: Vec<String>, op: F) -> Result<(), BitTorrentError>
+    where
+        F: Fn(Vec<String>) -> Fut,
+        Fut: Future<Output = Result<(), ClientError>>,
+    {
+        let batches: Vec<Vec<String>> = ids
+            .chunks(self.batch_size)
+            .map(<[String]>::to_vec)
+            .collect();
+        let total = batches.len();
+        let mut errors = Vec::new();
+        for (index, batch) in batches.into_iter().enumerate() {
+            debug!(
+                "Sending batch {} of {total} with {} ids",
+                index + 1,
+                batch.len()
+            );
+            if let Err(e) = self.call(|_| op(batch)).await {
+                debug!("Batch {} of {total} failed: {e:?}", index + 1);
+                errors.push(e);
+            }
+        }
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            failed => Err(BitTorrentError::ServerError(format!(
+                "{failed} of {total} batches failed: {}",
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ))),
+        }
+    }
+
+    /// Remove the given torrents in batches, see [`BitTorrent::remove`].
+    async fn remove_torrents(
+        &self,
+        ids: Vec<String>,
+        delete_local_data: bool,
+    ) -> Result<(), BitTorrentError> {
+        debug!("Removing torrents {ids:?}, delete_local_data={delete_local_data}");
+        self.in_batches(ids, |batch| {
+            self.client.torrent_remove(Some(batch), delete_local_data)
+        })
+        .await?;
+        debug!("Remove command sent");
+        Ok(())
+    }
+
+    /// Fetch the peer counts of torrent `id`, without its hash.
+    async fn torrent_peers(&self, id: i32) -> Result<TorrentPeers, BitTorrentError> {
+        self.call(|ops| ops.torrents_peers(Some(vec![id])))
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                BitTorrentError::InvalidTorrent(format!("No peers found for torrent ID {}", id))
+            })
+    }
+
+    /// Fetch the hashes of the given torrents in a single request, keyed by id.
+    async fn hashes_by_id(&self, ids: Vec<i32>) -> Result<HashMap<i32, String>, BitTorrentError> {
+        Ok(self
+            .call(|ops| ops.torrents(Some(ids)))
+            .await?
+            .into_iter()
+            .map(|t| (t.id, t.hash_string))
+            .collect())
+    }
+
+    /// The hash of torrent `id` in `hashes`, see [`TransmissionClient::hashes_by_id`].
+    fn hash_of(hashes: &HashMap<i32, String>, id: i32) -> Result<String, BitTorrentError> {
+        hashes.get(&id).cloned().ok_or_else(|| {
+            BitTorrentError::InvalidTorrent(format!("No torrent found with ID {}", id))
+        })
+    }
+
+    /// Fetch a single torrent by its hash, compared case-insensitively like [`TorrentKey`].
+    async fn torrent_by_hash(&self, hash: &str) -> Result<Torrent, BitTorrentError> {
+        let key = TorrentKey::new(hash);
+        self.call(|ops| ops.torrents(None))
+            .await?
+            .into_iter()
+            .find(|t| TorrentKey::new(&t.hash_string) == key)
+            .map(|t| TransmissionTorrentWrapper(t).into())
+            .ok_or_else(|| {
+                BitTorrentError::InvalidTorrent(format!("No torrent found with hash {}", hash))
+            })
     }
 }
 
@@ -62,10 +833,8 @@ impl<T: TransmissionOps> BitTorrent for TransmissionClient<T> {
     async fn add(&self, torrent_file: &str) -> Result<Torrent, BitTorrentError> {
         debug!("Adding torrent from file: {}", torrent_file);
         let torrent = self
-            .client
-            .torrent_add_filename(torrent_file)
-            .await
-            .map_err(map_client_error)?
+            .call(|ops| ops.torrent_add_filename(torrent_file))
+            .await?
             .ok_or_else(|| BitTorrentError::InvalidTorrent("No torrent returned".into()))?;
 
         debug!("Added {torrent:?}");
@@ -74,10 +843,8 @@ impl<T: TransmissionOps> BitTorrent for TransmissionClient<T> {
 
     async fn stop(&self, ids: Vec<String>) -> Result<(), BitTorrentError> {
         debug!("Stopping torrents {ids:?}");
-        self.client
-            .torrent_stop(Some(ids))
-            .await
-            .map_err(map_client_error)?;
+        self.in_batches(ids, |batch| self.client.torrent_stop(Some(batch)))
+            .await?;
         debug!("Stop command sent");
         Ok(())
     }
@@ -85,10 +852,8 @@ impl<T: TransmissionOps> BitTorrent for TransmissionClient<T> {
     async fn list(&self) -> Result<Vec<Torrent>, BitTorrentError> {
         debug!("Listing active torrents");
         let torrents = self
-            .client
-            .torrents(None)
-            .await
-            .map_err(map_client_error)?
+            .call(|ops| ops.torrents(None))
+            .await?
             .into_iter()
             .map(|t| TransmissionTorrentWrapper(t).into())
             .collect();
@@ -99,17 +864,11 @@ impl<T: TransmissionOps> BitTorrent for TransmissionClient<T> {
 
     async fn peers(&self, id: i32) -> Result<Peers, BitTorrentError> {
         debug!("Getting peers for torrent ID {id}");
-        let peers_vec = self
-            .client
-            .torrents_peers(Some(vec![id]))
-            .await
-            .map_err(map_client_error)?;
-        let peers = peers_vec.first().ok_or_else(|| {
-            BitTorrentError::InvalidTorrent(format!("No peers found for torrent ID {}", id))
-        })?;
-        debug!("Peers for torrent ID {id}: {peers:?}");
+        let peers = self.torrent_peers(id).await?;
+        let hash = Self::hash_of(&self.hashes_by_id(vec![id]).await?, id)?;
+        debug!("Peers for torrent ID {id} ({hash}): {peers:?}");
 
-        Ok(TransmissionTorrentPeersWrapper(peers.clone()).into())
+        Ok(TransmissionTorrentPeersWrapper(peers, hash).into())
     }
 
     async fn remove(
@@ -117,26 +876,91 @@ impl<T: TransmissionOps> BitTorrent for TransmissionClient<T> {
         ids: Vec<String>,
         delete_local_data: bool,
     ) -> Result<(), BitTorrentError> {
-        debug!("Removing torrents {ids:?}, delete_local_data={delete_local_data}");
-        self.client
-            .torrent_remove(Some(ids), delete_local_data)
-            .await
-            .map_err(map_client_error)?;
-        debug!("Remove command sent");
-        Ok(())
+        if delete_local_data && self.require_delete_confirmation {
+            return Err(BitTorrentError::Other(
+                "deleting local data requires remove_with_data".to_string(),
+            ));
+        }
+        self.remove_torrents(ids, delete_local_data).await
     }
 
     async fn stats(&self) -> Result<SessionStats, BitTorrentError> {
         debug!("Getting session statistics");
+        // Some minimal Transmission-compatible daemons do not implement session-stats.
         let stats = self
-            .client
-            .session_stats()
+            .call(|ops| ops.session_stats())
             .await
-            .map_err(map_client_error)?;
+            .map_err(|e| match e {
+                BitTorrentError::ServerError(msg) if msg == UNRECOGNIZED_METHOD => {
+                    BitTorrentError::Unsupported(format!("session-stats: {msg}"))
+                }
+                e => e,
+            })?;
         debug!("Session statistics: {stats:?}");
 
         Ok(TransmissionSessionStatsWrapper(stats).into())
     }
+
+    async fn ping(&self) -> Result<(), BitTorrentError> {
+        debug!("Pinging Transmission daemon");
+        // session-stats is cheap and read-only, but still requires an authorized session.
+        self.call(|ops| ops.session_stats()).await?;
+        debug!("Transmission daemon is healthy");
+        Ok(())
+    }
+
+    async fn set_honors_session_limits(
+        &self,
+        ids: Vec<String>,
+        honors: bool,
+    ) -> Result<(), BitTorrentError> {
+        debug!("Setting honors_session_limits={honors} for torrents {ids:?}");
+        let mutator = TorrentMutator {
+            honors_session_limits: Some(honors),
+            ..Default::default()
+        };
+        self.call(|ops| ops.torrent_set(Some(ids), mutator)).await?;
+        debug!("Honors session limits set");
+        Ok(())
+    }
+
+    async fn set_peer_limit(&self, ids: Vec<String>, limit: i32) -> Result<(), BitTorrentError> {
+        if limit < 0 {
+            return Err(BitTorrentError::Other(format!(
+                "peer limit must not be negative: {limit}"
+            )));
+        }
+
+        debug!("Setting peer limit of torrents {ids:?} to {limit}");
+        let mutator = TorrentMutator {
+            peer_limit: Some(limit),
+            ..Default::default()
+        };
+        self.call(|ops| ops.torrent_set(Some(ids), mutator)).await?;
+        debug!("Peer limit set");
+        Ok(())
+    }
+}
+
+/// An outstanding RPC call, see [`TransmissionClient::rpc`].
+struct InFlight<'a> {
+    outstanding: &'a AtomicUsize,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.outstanding.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Whether the RPC URL points at this host.
+fn is_local(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
 }
 
 /// Maps transmission client errors to BitTorrent errors.
@@ -144,6 +968,11 @@ fn map_client_error(err: ClientError) -> BitTorrentError {
     match err {
         ClientError::TransmissionUnauthorized => BitTorrentError::Unauthorized,
         ClientError::TransmissionError(msg) => BitTorrentError::ServerError(msg),
+        // A timeout while connecting means the daemon is unreachable, while a timeout after
+        // connecting means it is too slow to respond.
+        ClientError::NetworkError(e) if e.is_timeout() && !e.is_connect() => {
+            BitTorrentError::Timeout(e.to_string())
+        }
         ClientError::NetworkError(e) => BitTorrentError::Network(e.to_string()),
         ClientError::SerdeError(e) => BitTorrentError::Other(e.to_string()),
     }
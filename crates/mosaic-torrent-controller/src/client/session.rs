@@ -0,0 +1,276 @@
+//! Session-level settings of the Transmission daemon.
+
+use std::sync::{MutexGuard, PoisonError};
+
+use transmission_client::SessionMutator;
+
+use mosaic_torrent_types::{BitTorrentError, DaemonInfo};
+
+use super::TransmissionClient;
+use crate::logging::debug;
+use crate::ops::TransmissionOps;
+
+/// The last minute of a day, counted from midnight.
+const LAST_MINUTE_OF_DAY: u16 = 24 * 60 - 1;
+
+/// The `alt-speed-time-day` bitmask with every day of the week set.
+const ALL_DAYS: u8 = 0b111_1111;
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> TransmissionClient<T> {
+    /// Set the port the daemon listens on for incoming peers, and whether it should forward that
+    /// port on the router via UPnP/NAT-PMP.
+    pub async fn set_peer_port(
+        &self,
+        port: u16,
+        enable_port_forwarding: bool,
+    ) -> Result<(), BitTorrentError> {
+        debug!("Setting peer port to {port}, port_forwarding={enable_port_forwarding}");
+        let mutator = SessionMutator {
+            peer_port: Some(i32::from(port)),
+            port_forwarding_enabled: Some(enable_port_forwarding),
+            ..Default::default()
+        };
+        self.call(|ops| ops.session_set(mutator)).await?;
+        debug!("Peer port set");
+        Ok(())
+    }
+
+    /// Get the port the daemon listens on for incoming peers.
+    pub async fn peer_port(&self) -> Result<u16, BitTorrentError> {
+        debug!("Getting peer port");
+        let session = self.call(|ops| ops.session()).await?;
+        let port = u16::try_from(session.peer_port).map_err(|_| {
+            BitTorrentError::ServerError(format!("invalid peer port {}", session.peer_port))
+        })?;
+        debug!("Peer port: {port}");
+
+        Ok(port)
+    }
+
+    /// Get the daemon's version and configuration directory, for diagnostics.
+    pub async fn daemon_info(&self) -> Result<DaemonInfo, BitTorrentError> {
+        debug!("Getting daemon info");
+        let session = self.call(|ops| ops.session()).await?;
+        let info = DaemonInfo {
+            version: session.version,
+            rpc_version: i64::from(session.rpc_version),
+            config_dir: session.config_dir,
+        };
+        debug!("Daemon info: {info:?}");
+
+        Ok(info)
+    }
+
+    /// Get the daemon's download directory.
+    ///
+    /// The directory is fetched once and cached on the client, shared by all its clones. Use
+    /// [`TransmissionClient::clear_download_dir_cache`] if it may have been changed elsewhere.
+    pub async fn download_dir(&self) -> Result<String, BitTorrentError> {
+        if let Some(download_dir) = self.cached_dirs().download_dir.clone() {
+            return Ok(download_dir);
+        }
+
+        Ok(self.fetch_session_dirs().await?.download_dir)
+    }
+
+    /// Get the daemon's directory for incomplete downloads, or `None` if it keeps incomplete
+    /// downloads in the download directory. Cached like [`TransmissionClient::download_dir`].
+    pub async fn incomplete_dir(&self) -> Result<Option<String>, BitTorrentError> {
+        if let Some(incomplete_dir) = self.cached_dirs().incomplete_dir.clone() {
+            return Ok(incomplete_dir);
+        }
+
+        Ok(self.fetch_session_dirs().await?.incomplete_dir)
+    }
+
+    /// Set the daemon's download directory.
+    ///
+    /// With `create`, the directory is created first if it does not exist, so that adding
+    /// torrents does not fail later on. This only happens when the daemon runs on this host, as
+    /// the path refers to the daemon's file system; for a remote daemon it is just set.
+    pub async fn set_download_dir(&self, path: &str, create: bool) -> Result<(), BitTorrentError> {
+        if create && self.daemon_is_local {
+            debug!("Creating download directory {path}");
+            tokio::fs::create_dir_all(path).await.map_err(|e| {
+                BitTorrentError::FileSystem(format!(
+                    "failed to create download directory {}: {}",
+                    path, e
+                ))
+            })?;
+        }
+
+        debug!("Setting download directory to {path}");
+        let mutator = SessionMutator {
+            download_dir: Some(path.to_owned()),
+            ..Default::default()
+        };
+        self.call(|ops| ops.session_set(mutator)).await?;
+        self.cached_dirs().download_dir = Some(path.to_owned());
+        debug!("Download directory set");
+        Ok(())
+    }
+
+    /// Forget the cached download and incomplete directories, so the next
+    /// [`TransmissionClient::download_dir`] fetches them from the daemon again.
+    pub fn clear_download_dir_cache(&self) {
+        *self.cached_dirs() = SessionDirs::default();
+    }
+
+    /// Ask the daemon to check whether its peer port is reachable from the outside.
+    pub async fn test_port(&self) -> Result<bool, BitTorrentError> {
+        debug!("Testing peer port");
+        let open = self.call(|ops| ops.port_test()).await?;
+        debug!("Peer port open: {open}");
+
+        Ok(open)
+    }
+
+    /// Get the free space in bytes at `path` on the daemon's host.
+    pub async fn free_space(&self, path: &str) -> Result<i64, BitTorrentError> {
+        debug!("Getting free space at {path}");
+        let free = self.call(|ops| ops.free_space(path.to_owned())).await?;
+        debug!("Free space at {path}: {free} bytes");
+
+        Ok(free)
+    }
+
+    /// Set whether the daemon appends `.part` to the names of incomplete files.
+    pub async fn set_rename_partial_files(&self, enabled: bool) -> Result<(), BitTorrentError> {
+        debug!("Setting rename_partial_files={enabled}");
+        let mutator = SessionMutator {
+            rename_partial_files: Some(enabled),
+            ..Default::default()
+        };
+        self.call(|ops| ops.session_set(mutator)).await?;
+        debug!("Rename partial files set");
+        Ok(())
+    }
+
+    /// Set the global download and upload speed limits of the daemon in KB/s. `None` disables
+    /// the limit in that direction.
+    pub async fn set_session_speed_limits(
+        &self,
+        down_kbps: Option<i32>,
+        up_kbps: Option<i32>,
+    ) -> Result<(), BitTorrentError> {
+        debug!("Setting session speed limits down={down_kbps:?} up={up_kbps:?}");
+        let mutator = SessionMutator {
+            speed_limit_down: down_kbps,
+            speed_limit_down_enabled: Some(down_kbps.is_some()),
+            speed_limit_up: up_kbps,
+            speed_limit_up_enabled: Some(up_kbps.is_some()),
+            ..Default::default()
+        };
+        self.call(|ops| ops.session_set(mutator)).await?;
+        debug!("Session speed limits set");
+        Ok(())
+    }
+
+    /// Set the daily schedule during which the daemon uses its alternative speed limits.
+    ///
+    /// `begin_minute` and `end_minute` are minutes after midnight, from 0 to 1439. `days` is a
+    /// bitmask of the days the schedule applies on: Sunday is `1`, Monday `2`, and so on up to
+    /// Saturday `64`, so `62` is weekdays and `127` every day.
+    pub async fn set_alt_speed_schedule(
+        &self,
+        enabled: bool,
+        begin_minute: u16,
+        end_minute: u16,
+        days: u8,
+    ) -> Result<(), BitTorrentError> {
+        for minute in [begin_minute, end_minute] {
+            if minute > LAST_MINUTE_OF_DAY {
+                return Err(BitTorrentError::Other(format!(
+                    "minute {minute} is out of range, must be between 0 and {LAST_MINUTE_OF_DAY}"
+                )));
+            }
+        }
+        if days > ALL_DAYS {
+            return Err(BitTorrentError::Other(format!(
+                "invalid days bitmask {days}, must be at most {ALL_DAYS}"
+            )));
+        }
+
+        debug!(
+            "Setting alt speed schedule {begin_minute}-{end_minute} on days {days}, enabled={enabled}"
+        );
+        let mutator = SessionMutator {
+            alt_speed_time_enabled: Some(enabled),
+            alt_speed_time_begin: Some(i32::from(begin_minute)),
+            alt_speed_time_end: Some(i32::from(end_minute)),
+            alt_speed_time_day: Some(i32::from(days)),
+            ..Default::default()
+        };
+        self.call(|ops| ops.session_set(mutator)).await?;
+        debug!("Alt speed schedule set");
+        Ok(())
+    }
+
+    /// Make the daemon download its blocklist again, returning the number of rules it holds.
+    pub async fn blocklist_update(&self) -> Result<i32, BitTorrentError> {
+        debug!("Updating blocklist");
+        let rules = self.call(|ops| ops.blocklist_update()).await?;
+        debug!("Blocklist updated with {rules} rules");
+
+        Ok(rules)
+    }
+
+    /// Enable or disable the peer blocklist. `url` replaces the URL it is downloaded from, `None`
+    /// keeps the current one.
+    pub async fn set_blocklist(
+        &self,
+        url: Option<&str>,
+        enabled: bool,
+    ) -> Result<(), BitTorrentError> {
+        debug!("Setting blocklist enabled={enabled}, url={url:?}");
+        let mutator = SessionMutator {
+            blocklist_url: url.map(str::to_owned),
+            blocklist_enabled: Some(enabled),
+            ..Default::default()
+        };
+        self.call(|ops| ops.session_set(mutator)).await?;
+        debug!("Blocklist set");
+        Ok(())
+    }
+
+    /// Fetch the daemon's directories and cache them.
+    async fn fetch_session_dirs(&self) -> Result<FetchedDirs, BitTorrentError> {
+        debug!("Getting session directories");
+        let session = self.call(|ops| ops.session()).await?;
+        let dirs = FetchedDirs {
+            download_dir: session.download_dir,
+            incomplete_dir: session
+                .incomplete_dir_enabled
+                .then_some(session.incomplete_dir),
+        };
+        debug!("Session directories: {dirs:?}");
+
+        let mut cached = self.cached_dirs();
+        cached.download_dir = Some(dirs.download_dir.clone());
+        cached.incomplete_dir = Some(dirs.incomplete_dir.clone());
+        Ok(dirs)
+    }
+
+    fn cached_dirs(&self) -> MutexGuard<'_, SessionDirs> {
+        // The cache only ever holds complete values, so a poisoned lock is still usable.
+        self.session_dirs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The daemon's directories, cached on the client after the first lookup.
+#[derive(Debug, Default)]
+pub(super) struct SessionDirs {
+    download_dir: Option<String>,
+    /// `Some(None)` once fetched from a daemon that has no incomplete directory enabled.
+    incomplete_dir: Option<Option<String>>,
+}
+
+/// The daemon's directories as fetched from the session.
+#[derive(Debug)]
+struct FetchedDirs {
+    download_dir: String,
+    incomplete_dir: Option<String>,
+}
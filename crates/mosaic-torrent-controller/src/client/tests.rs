@@ -1,11 +1,182 @@
 //! Tests for the TransmissionClient.
-
-use mosaic_torrent_types::{BitTorrent, BitTorrentError};
+//!
+//! These tests do not depend on logging. Besides the default build, run them with
+//! `cargo test -p mosaic-torrent-controller --no-default-features` to check that the crate still
+//! compiles and works without the `tracing` feature.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use mosaic_torrent_types::{
+    AddOptions, BitTorrent, BitTorrentError, SpeedLimits, TorrentStatus, VerifyMode,
+};
+use tokio_util::sync::CancellationToken;
 use transmission_client::ClientError;
 
-use super::{TransmissionClient, map_client_error};
+use super::{TransmissionClient, is_local, map_client_error};
 use crate::ops::MockTransmissionOps;
-use crate::testutil::{make_test_peers, make_test_stats, make_test_torrent};
+use crate::poll::PollStrategy;
+use crate::testutil::{
+    SlowOps, make_added_torrent, make_network_error, make_request_timeout_error, make_test_files,
+    make_test_peers, make_test_session, make_test_stats, make_test_torrent,
+};
+
+#[tokio::test]
+async fn test_try_with_client_applies_session_settings() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_session_set()
+        .withf(|mutator| mutator.download_queue_size == Some(3))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let result = TransmissionClient::try_with_client(mock, 3).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_try_with_client_saturates_queue_size() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_session_set()
+        .withf(|mutator| mutator.download_queue_size == Some(i32::MAX))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let result = TransmissionClient::try_with_client(mock, u32::MAX).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_try_with_client_accepts_transmission_3() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| {
+        let mut session = make_test_session();
+        session.rpc_version = 16;
+        session.version = "3.00 (bb6b5a062e)".to_string();
+        Ok(session)
+    });
+    mock.expect_session_set().returning(|_| Ok(()));
+
+    assert!(TransmissionClient::try_with_client(mock, 3).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_try_with_client_rejects_old_version() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| {
+        let mut session = make_test_session();
+        session.rpc_version = 15;
+        session.version = "2.94 (d8e60ee44f)".to_string();
+        Ok(session)
+    });
+    mock.expect_session_set().never();
+
+    let result = TransmissionClient::try_with_client(mock, 3).await;
+
+    match result {
+        Err(BitTorrentError::UnsupportedVersion { found, minimum }) => {
+            assert_eq!(found, "15");
+            assert_eq!(minimum, "16");
+        }
+        Err(other) => panic!("Expected UnsupportedVersion, got {other:?}"),
+        Ok(_) => panic!("Expected old version to be rejected"),
+    }
+}
+
+#[tokio::test]
+async fn test_try_with_client_waiting_retries_until_ready() {
+    let mut mock = MockTransmissionOps::new();
+    let failures = Mutex::new(vec![make_network_error().await, make_network_error().await]);
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_session_set()
+        .times(3)
+        .returning(move |_| match failures.lock().unwrap().pop() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        });
+
+    let result =
+        TransmissionClient::try_with_client_waiting(mock, 3, Duration::from_secs(10)).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_try_with_client_waiting_retries_within_short_timeout() {
+    let mut mock = MockTransmissionOps::new();
+    let failures = Mutex::new(vec![make_network_error().await]);
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_session_set()
+        .times(2)
+        .returning(move |_| match failures.lock().unwrap().pop() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        });
+
+    // Shorter than the poll interval, but still enough for a second attempt.
+    let result =
+        TransmissionClient::try_with_client_waiting(mock, 3, Duration::from_millis(100)).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_try_with_client_waiting_times_out() {
+    let mut mock = MockTransmissionOps::new();
+    let mut errors = Vec::new();
+    for _ in 0..5 {
+        errors.push(make_network_error().await);
+    }
+    let failures = Mutex::new(errors);
+
+    mock.expect_session()
+        .returning(move || Err(failures.lock().unwrap().pop().unwrap()));
+
+    let result =
+        TransmissionClient::try_with_client_waiting(mock, 3, Duration::from_millis(300)).await;
+
+    assert!(matches!(result, Err(BitTorrentError::Timeout(_))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_try_with_client_waiting_gives_up_on_hanging_daemon() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_session_set().returning(|_| Ok(()));
+
+    // The daemon accepts the request, but only answers long after the wait timeout.
+    let ops = SlowOps::new(mock, Duration::from_secs(3600));
+    let start = tokio::time::Instant::now();
+    let result = TransmissionClient::try_with_client_waiting(ops, 3, Duration::from_secs(5)).await;
+
+    assert!(matches!(result, Err(BitTorrentError::Timeout(_))));
+    assert!(start.elapsed() < Duration::from_secs(6));
+}
+
+#[tokio::test]
+async fn test_try_with_client_waiting_does_not_retry_unauthorized() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session()
+        .times(1)
+        .returning(|| Err(ClientError::TransmissionUnauthorized));
+
+    let result =
+        TransmissionClient::try_with_client_waiting(mock, 3, Duration::from_secs(10)).await;
+
+    assert!(matches!(result, Err(BitTorrentError::Unauthorized)));
+}
 
 #[tokio::test]
 async fn test_add_torrent_success() {
@@ -80,6 +251,216 @@ async fn test_add_torrent_server_error() {
     }
 }
 
+#[tokio::test]
+async fn test_add_with_options_always_verifies() {
+    let mut mock = MockTransmissionOps::new();
+    let mut seq = mockall::Sequence::new();
+
+    mock.expect_torrent_add_filename()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(Some(make_test_torrent(1, "test_torrent", "abc123"))));
+    mock.expect_torrent_verify()
+        .withf(|ids| ids == &Some(vec!["abc123".to_string()]))
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let options = AddOptions {
+        verify_on_add: VerifyMode::Always,
+    };
+    let torrent = client
+        .add_with_options("/path/to/file.torrent", &options)
+        .await
+        .unwrap();
+    assert_eq!(torrent.hash_string, "abc123");
+}
+
+#[tokio::test]
+async fn test_add_with_options_skips_verify() {
+    for verify_on_add in [VerifyMode::Auto, VerifyMode::Never] {
+        let mut mock = MockTransmissionOps::new();
+        mock.expect_torrent_add_filename()
+            .returning(|_| Ok(Some(make_test_torrent(1, "test_torrent", "abc123"))));
+        mock.expect_torrent_verify().never();
+
+        let client = TransmissionClient::with_client(mock);
+        let options = AddOptions { verify_on_add };
+        assert!(
+            client
+                .add_with_options("/path/to/file.torrent", &options)
+                .await
+                .is_ok()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_add_detailed_fresh() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .withf(|ids| ids.is_none())
+        .returning(|_| Ok(vec![]));
+    mock.expect_torrent_add_filename()
+        .returning(|_| Ok(Some(make_added_torrent(1, "test_torrent", "abc123"))));
+    mock.expect_torrents()
+        .withf(|ids| ids.as_deref() == Some(&[1][..]))
+        .times(1)
+        .returning(|_| Ok(vec![make_test_torrent(1, "test_torrent", "abc123")]));
+
+    let client = TransmissionClient::with_client(mock);
+    let added = client.add_detailed("/path/to/file.torrent").await.unwrap();
+
+    assert_eq!(added.torrent.hash_string, "abc123");
+    assert_eq!(added.torrent.download_dir, "/downloads");
+    assert!(added.metadata_complete);
+    assert!(!added.duplicate);
+}
+
+#[tokio::test]
+async fn test_add_detailed_magnet_pending() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .withf(|ids| ids.is_none())
+        .returning(|_| Ok(vec![]));
+    mock.expect_torrent_add_filename()
+        .returning(|_| Ok(Some(make_added_torrent(1, "", "abc123"))));
+    mock.expect_torrents()
+        .withf(|ids| ids.as_deref() == Some(&[1][..]))
+        .returning(|_| {
+            let mut torrent = make_test_torrent(1, "", "abc123");
+            torrent.metadata_percent_complete = 0.0;
+            Ok(vec![torrent])
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let added = client
+        .add_detailed("magnet:?xt=urn:btih:abc123")
+        .await
+        .unwrap();
+
+    assert!(!added.metadata_complete);
+    assert!(!added.duplicate);
+}
+
+#[tokio::test]
+async fn test_add_detailed_duplicate() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .returning(|_| Ok(vec![make_test_torrent(1, "test_torrent", "abc123")]));
+    mock.expect_torrent_add_filename()
+        .returning(|_| Ok(Some(make_added_torrent(1, "test_torrent", "abc123"))));
+
+    let client = TransmissionClient::with_client(mock);
+    let added = client.add_detailed("/path/to/file.torrent").await.unwrap();
+
+    assert!(added.metadata_complete);
+    assert!(added.duplicate);
+}
+
+#[tokio::test]
+async fn test_cloned_clients_share_inner_client() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .times(2)
+        .returning(|_| Ok(vec![make_test_torrent(1, "torrent1", "hash1")]));
+
+    let client = TransmissionClient::with_client(mock);
+    let clone = client.clone();
+
+    assert_eq!(client.list().await.unwrap().len(), 1);
+    assert_eq!(clone.list().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_free_space() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_free_space()
+        .withf(|path| path == "/downloads")
+        .returning(|_| Ok(1_000_000));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert_eq!(client.free_space("/downloads").await.unwrap(), 1_000_000);
+}
+
+#[tokio::test]
+async fn test_add_if_space_sufficient() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_free_space()
+        .withf(|path| path == "/incomplete" || path == "/downloads")
+        .times(2)
+        .returning(|_| Ok(i64::MAX));
+    mock.expect_torrent_add_filename()
+        .times(1)
+        .returning(|_| Ok(Some(make_test_torrent(1, "test_folder", "abc123"))));
+
+    let client = TransmissionClient::with_client(mock);
+    let torrent = client
+        .add_if_space("assets/test_folder.torrent")
+        .await
+        .unwrap();
+
+    assert_eq!(torrent.hash_string, "abc123");
+}
+
+#[tokio::test]
+async fn test_add_if_space_insufficient() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_free_space().returning(|_| Ok(1024));
+    mock.expect_torrent_add_filename().never();
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.add_if_space("assets/test_folder.torrent").await;
+
+    match result.unwrap_err() {
+        BitTorrentError::InsufficientSpace {
+            required,
+            available,
+        } => {
+            assert!(required > available);
+            assert_eq!(available, 1024);
+        }
+        other => panic!("Expected InsufficientSpace error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_add_if_space_incomplete_dir_full() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_free_space().returning(|path| {
+        if path == "/incomplete" {
+            Ok(1024)
+        } else {
+            Ok(i64::MAX)
+        }
+    });
+    mock.expect_torrent_add_filename().never();
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.add_if_space("assets/test_folder.torrent").await;
+
+    assert!(matches!(
+        result,
+        Err(BitTorrentError::InsufficientSpace {
+            available: 1024,
+            ..
+        })
+    ));
+}
+
 #[tokio::test]
 async fn test_stop_torrent_success() {
     let mut mock = MockTransmissionOps::new();
@@ -169,6 +550,33 @@ async fn test_list_torrents_error() {
     }
 }
 
+#[tokio::test]
+async fn test_list_grouped() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().times(1).returning(|_| {
+        let mut seeding = make_test_torrent(2, "torrent2", "hash2");
+        seeding.status = 6;
+        let mut also_seeding = make_test_torrent(3, "torrent3", "hash3");
+        also_seeding.status = 6;
+        Ok(vec![
+            make_test_torrent(1, "torrent1", "hash1"),
+            seeding,
+            also_seeding,
+        ])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let grouped = client.list_grouped().await.unwrap();
+
+    assert_eq!(grouped.len(), TorrentStatus::ALL.len());
+    assert_eq!(grouped[&TorrentStatus::Downloading].len(), 1);
+    assert_eq!(grouped[&TorrentStatus::Downloading][0].id, 1);
+    assert_eq!(grouped[&TorrentStatus::Seeding].len(), 2);
+    assert!(grouped[&TorrentStatus::Stopped].is_empty());
+    assert!(grouped[&TorrentStatus::Verifying].is_empty());
+}
+
 #[tokio::test]
 async fn test_peers_success() {
     let mut mock = MockTransmissionOps::new();
@@ -176,6 +584,9 @@ async fn test_peers_success() {
     mock.expect_torrents_peers()
         .withf(|ids| ids == &Some(vec![1]))
         .returning(|_| Ok(vec![make_test_peers(1)]));
+    mock.expect_torrents()
+        .withf(|ids| ids == &Some(vec![1]))
+        .returning(|_| Ok(vec![make_test_torrent(1, "test_torrent", "abc123")]));
 
     let client = TransmissionClient::with_client(mock);
     let result = client.peers(1).await;
@@ -183,6 +594,7 @@ async fn test_peers_success() {
     assert!(result.is_ok());
     let peers = result.unwrap();
     assert_eq!(peers.id, 1);
+    assert_eq!(peers.hash_string, "abc123");
     assert_eq!(peers.peers_connected, 5);
     assert_eq!(peers.peers_getting_from_us, 2);
     assert_eq!(peers.peers_sending_to_us, 3);
@@ -225,6 +637,29 @@ async fn test_peers_error() {
     }
 }
 
+#[tokio::test]
+async fn test_total_peers() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents_peers()
+        .withf(|ids| ids.is_none())
+        .times(1)
+        .returning(|_| {
+            let mut second = make_test_peers(2);
+            second.peers_connected = 7;
+            second.peers_sending_to_us = 1;
+            second.peers_getting_from_us = 4;
+            Ok(vec![make_test_peers(1), second])
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let totals = client.total_peers().await.unwrap();
+
+    assert_eq!(totals.connected, 12);
+    assert_eq!(totals.sending_to_us, 4);
+    assert_eq!(totals.getting_from_us, 6);
+}
+
 #[tokio::test]
 async fn test_remove_torrent_success() {
     let mut mock = MockTransmissionOps::new();
@@ -253,6 +688,57 @@ async fn test_remove_torrent_without_delete() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_remove_keep_data_never_deletes() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrent_remove()
+        .withf(|ids, delete_data| {
+            ids == &Some(vec!["hash1".to_string(), "hash2".to_string()]) && !*delete_data
+        })
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock).with_delete_confirmation(true);
+    let result = client
+        .remove_keep_data(vec!["hash1".to_string(), "hash2".to_string()])
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_remove_with_data_deletes() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrent_remove()
+        .withf(|ids, delete_data| ids == &Some(vec!["hash1".to_string()]) && *delete_data)
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock).with_delete_confirmation(true);
+    let result = client.remove_with_data(vec!["hash1".to_string()]).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_remove_requires_confirmation_to_delete() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrent_remove()
+        .withf(|_, delete_data| !*delete_data)
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock).with_delete_confirmation(true);
+    let result = client.remove(vec!["hash1".to_string()], true).await;
+    assert!(matches!(result, Err(BitTorrentError::Other(_))));
+
+    let result = client.remove(vec!["hash1".to_string()], false).await;
+    assert!(result.is_ok());
+}
+
 #[tokio::test]
 async fn test_remove_torrent_error() {
     let mut mock = MockTransmissionOps::new();
@@ -309,17 +795,1475 @@ async fn test_stats_error() {
     }
 }
 
-#[test]
-fn test_error_mapping_unauthorized() {
-    let err = map_client_error(ClientError::TransmissionUnauthorized);
-    assert!(matches!(err, BitTorrentError::Unauthorized));
-}
+#[tokio::test]
+async fn test_stats_unsupported() {
+    let mut mock = MockTransmissionOps::new();
 
-#[test]
-fn test_error_mapping_server_error() {
-    let err = map_client_error(ClientError::TransmissionError("test error".to_string()));
-    match err {
-        BitTorrentError::ServerError(msg) => assert_eq!(msg, "test error"),
-        _ => panic!("Expected ServerError"),
+    mock.expect_session_stats().returning(|| {
+        Err(ClientError::TransmissionError(
+            "method name not recognized".to_string(),
+        ))
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.stats().await;
+
+    match result.unwrap_err() {
+        BitTorrentError::Unsupported(msg) => assert!(msg.contains("session-stats")),
+        other => panic!("Expected Unsupported error, got {other:?}"),
     }
 }
+
+#[tokio::test]
+async fn test_snapshot() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().times(1).returning(|_| {
+        Ok(vec![
+            make_test_torrent(1, "torrent1", "hash1"),
+            make_test_torrent(2, "torrent2", "hash2"),
+        ])
+    });
+    mock.expect_session_stats()
+        .times(1)
+        .returning(|| Ok(make_test_stats()));
+
+    let client = TransmissionClient::with_client(mock);
+    let before = SystemTime::now();
+    let snapshot = client.snapshot().await.unwrap();
+
+    assert_eq!(snapshot.torrents.len(), 2);
+    assert_eq!(snapshot.stats.torrent_count, 1);
+    assert!(snapshot.captured_at >= before);
+    assert!(snapshot.captured_at <= SystemTime::now());
+}
+
+#[tokio::test]
+async fn test_ping_healthy() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_stats()
+        .times(1)
+        .returning(|| Ok(make_test_stats()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.ping().await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_ping_unauthorized() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_stats()
+        .returning(|| Err(ClientError::TransmissionUnauthorized));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.ping().await;
+
+    assert!(matches!(result, Err(BitTorrentError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn test_ping_unreachable() {
+    let mut mock = MockTransmissionOps::new();
+    let network_error = make_network_error().await;
+
+    mock.expect_session_stats()
+        .return_once(move || Err(network_error));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.ping().await;
+
+    assert!(matches!(result, Err(BitTorrentError::Network(_))));
+}
+
+#[tokio::test]
+async fn test_wait_until_complete_success() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().returning(|_| {
+        let mut torrent = make_test_torrent(1, "test_torrent", "abc123");
+        torrent.percent_done = 1.0;
+        Ok(vec![torrent])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .wait_until_complete(
+            "abc123",
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+            None,
+        )
+        .await;
+
+    assert_eq!(result.unwrap().hash_string, "abc123");
+}
+
+#[tokio::test]
+async fn test_wait_until_complete_ignores_hash_case() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().returning(|_| {
+        let mut torrent = make_test_torrent(1, "test_torrent", "abc123");
+        torrent.percent_done = 1.0;
+        Ok(vec![torrent])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .wait_until_complete(
+            "ABC123",
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+            None,
+        )
+        .await;
+
+    assert_eq!(result.unwrap().hash_string, "abc123");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wait_until_complete_polls_by_id() {
+    let mut mock = MockTransmissionOps::new();
+
+    // The full list is only fetched to find the torrent, later polls ask for its id alone.
+    mock.expect_torrents()
+        .withf(|ids| ids.is_none())
+        .times(1)
+        .returning(|_| {
+            Ok(vec![
+                make_test_torrent(1, "other", "def456"),
+                make_test_torrent(2, "test_torrent", "abc123"),
+            ])
+        });
+    mock.expect_torrents()
+        .withf(|ids| ids.as_deref() == Some(&[2][..]))
+        .times(1)
+        .returning(|_| {
+            let mut torrent = make_test_torrent(2, "test_torrent", "abc123");
+            torrent.percent_done = 1.0;
+            Ok(vec![torrent])
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .wait_until_complete(
+            "abc123",
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+            None,
+        )
+        .await;
+
+    assert_eq!(result.unwrap().hash_string, "abc123");
+}
+
+#[tokio::test]
+async fn test_wait_until_complete_timeout() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .returning(|_| Ok(vec![make_test_torrent(1, "test_torrent", "abc123")]));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .wait_until_complete(
+            "abc123",
+            Duration::from_millis(10),
+            Duration::from_millis(50),
+            None,
+        )
+        .await;
+
+    assert!(matches!(result, Err(BitTorrentError::Timeout(_))));
+}
+
+#[tokio::test]
+async fn test_wait_until_complete_exponential_backoff() {
+    let mut mock = MockTransmissionOps::new();
+
+    // Complete on the fourth poll, after waiting 10 + 20 + 20 ms.
+    let polls = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&polls);
+    mock.expect_torrents().returning(move |_| {
+        let mut polls = counter.lock().unwrap();
+        *polls += 1;
+        let mut torrent = make_test_torrent(1, "test_torrent", "abc123");
+        if *polls == 4 {
+            torrent.percent_done = 1.0;
+        }
+        Ok(vec![torrent])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let started = Instant::now();
+    let result = client
+        .wait_until_complete(
+            "abc123",
+            PollStrategy::Exponential {
+                base: Duration::from_millis(10),
+                max: Duration::from_millis(20),
+            },
+            Duration::from_secs(5),
+            None,
+        )
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(*polls.lock().unwrap(), 4);
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wait_for_metadata() {
+    let mut mock = MockTransmissionOps::new();
+
+    let polls = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&polls);
+    mock.expect_torrents().returning(move |_| {
+        let mut polls = counter.lock().unwrap();
+        *polls += 1;
+        let mut torrent = make_test_torrent(1, "magnet", "abc123");
+        torrent.metadata_percent_complete = if *polls == 1 { 0.5 } else { 1.0 };
+        Ok(vec![torrent])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let torrent = client
+        .wait_for_metadata("abc123", Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    assert_eq!(torrent.metadata_percent_complete, 1.0);
+    assert_eq!(*polls.lock().unwrap(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_wait_for_metadata_timeout() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().returning(|_| {
+        let mut torrent = make_test_torrent(1, "magnet", "abc123");
+        torrent.metadata_percent_complete = 0.0;
+        Ok(vec![torrent])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .wait_for_metadata("abc123", Duration::from_secs(5))
+        .await;
+
+    match result {
+        Err(BitTorrentError::Timeout(msg)) => assert!(msg.contains("metadata")),
+        other => panic!("Expected Timeout error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_wait_until_complete_cancelled() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .returning(|_| Ok(vec![make_test_torrent(1, "test_torrent", "abc123")]));
+
+    let token = CancellationToken::new();
+    let trigger = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        trigger.cancel();
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let started = Instant::now();
+    let result = client
+        .wait_until_complete(
+            "abc123",
+            Duration::from_millis(10),
+            Duration::from_secs(30),
+            Some(&token),
+        )
+        .await;
+
+    assert!(matches!(result, Err(BitTorrentError::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_set_peer_port() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.peer_port == Some(6881) && mutator.port_forwarding_enabled == Some(true)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.set_peer_port(6881, true).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_peer_port() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| Ok(make_test_session()));
+
+    let client = TransmissionClient::with_client(mock);
+    let port = client.peer_port().await.unwrap();
+
+    assert_eq!(port, 51413);
+}
+
+#[tokio::test]
+async fn test_peer_port_error() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session()
+        .returning(|| Err(ClientError::TransmissionUnauthorized));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.peer_port().await;
+
+    assert!(matches!(result, Err(BitTorrentError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn test_set_rename_partial_files_enable() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| mutator.rename_partial_files == Some(true))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(client.set_rename_partial_files(true).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_rename_partial_files_disable() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| mutator.rename_partial_files == Some(false))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(client.set_rename_partial_files(false).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_session_speed_limits_both() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.speed_limit_down == Some(500)
+                && mutator.speed_limit_down_enabled == Some(true)
+                && mutator.speed_limit_up == Some(100)
+                && mutator.speed_limit_up_enabled == Some(true)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(
+        client
+            .set_session_speed_limits(Some(500), Some(100))
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_set_session_speed_limits_download_only() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.speed_limit_down == Some(500)
+                && mutator.speed_limit_down_enabled == Some(true)
+                && mutator.speed_limit_up.is_none()
+                && mutator.speed_limit_up_enabled == Some(false)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(
+        client
+            .set_session_speed_limits(Some(500), None)
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_set_session_speed_limits_upload_only() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.speed_limit_down.is_none()
+                && mutator.speed_limit_down_enabled == Some(false)
+                && mutator.speed_limit_up == Some(100)
+                && mutator.speed_limit_up_enabled == Some(true)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(
+        client
+            .set_session_speed_limits(None, Some(100))
+            .await
+            .is_ok()
+    );
+}
+
+#[tokio::test]
+async fn test_set_session_speed_limits_disabled() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.speed_limit_down_enabled == Some(false)
+                && mutator.speed_limit_up_enabled == Some(false)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(client.set_session_speed_limits(None, None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_alt_speed_schedule() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.alt_speed_time_enabled == Some(true)
+                && mutator.alt_speed_time_begin == Some(0)
+                && mutator.alt_speed_time_end == Some(1439)
+                && mutator.alt_speed_time_day == Some(62)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.set_alt_speed_schedule(true, 0, 1439, 62).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_alt_speed_schedule_out_of_range() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set().never();
+
+    let client = TransmissionClient::with_client(mock);
+
+    for (begin, end, days) in [(1440, 0, 127), (0, 1440, 127), (0, 60, 128)] {
+        let result = client.set_alt_speed_schedule(true, begin, end, days).await;
+        assert!(
+            matches!(result, Err(BitTorrentError::Other(_))),
+            "expected ({begin}, {end}, {days}) to be rejected, got {result:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_port_open() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_port_test().returning(|| Ok(true));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(client.test_port().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_port_closed() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_port_test().returning(|| Ok(false));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert!(!client.test_port().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_port_error() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_port_test().returning(|| {
+        Err(ClientError::TransmissionError(
+            "port test failed".to_string(),
+        ))
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.test_port().await;
+
+    match result.unwrap_err() {
+        BitTorrentError::ServerError(msg) => assert_eq!(msg, "port test failed"),
+        other => panic!("Expected ServerError, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_file_progress_complete() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents_files()
+        .withf(|ids| ids == &Some(vec![1]))
+        .returning(|_| {
+            Ok(vec![make_test_files(
+                1,
+                &[("folder/a.bin", 1000, 1000), ("folder/b.bin", 2000, 500)],
+            )])
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let progress = client.file_progress(1, 0).await.unwrap();
+
+    assert_eq!(progress, 1.0);
+}
+
+#[tokio::test]
+async fn test_file_progress_partial() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents_files().returning(|_| {
+        Ok(vec![make_test_files(
+            1,
+            &[("folder/a.bin", 1000, 1000), ("folder/b.bin", 2000, 500)],
+        )])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let progress = client.file_progress(1, 1).await.unwrap();
+
+    assert_eq!(progress, 0.25);
+}
+
+#[tokio::test]
+async fn test_file_progress_bad_index() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents_files()
+        .returning(|_| Ok(vec![make_test_files(1, &[("folder/a.bin", 1000, 1000)])]));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.file_progress(1, 5).await;
+
+    match result.unwrap_err() {
+        BitTorrentError::InvalidTorrent(msg) => {
+            assert!(msg.contains("No file with index 5"));
+        }
+        other => panic!("Expected InvalidTorrent error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_set_files_wanted_by_path() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents_files().returning(|_| {
+        Ok(vec![make_test_files(
+            1,
+            &[
+                ("folder/movie.mkv", 1000, 0),
+                ("folder/samples/a.mkv", 100, 0),
+                ("folder/samples/keep.mkv", 100, 0),
+                ("folder/samples2/b.mkv", 100, 0),
+                ("folder/extras/c.txt", 10, 0),
+            ],
+        )])
+    });
+    mock.expect_torrents()
+        .withf(|ids| ids == &Some(vec![1]))
+        .returning(|_| Ok(vec![make_test_torrent(1, "folder", "abc123")]));
+    mock.expect_torrent_set()
+        .withf(|ids, mutator| {
+            ids.as_deref() == Some(&["abc123".to_string()][..])
+                && mutator.files_wanted == Some(vec![0, 2])
+                && mutator.files_unwanted == Some(vec![1])
+        })
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .set_files_wanted_by_path(
+            1,
+            vec![
+                "folder/movie.mkv".to_string(),
+                "folder/samples/keep.mkv".to_string(),
+            ],
+            vec!["folder/samples/".to_string()],
+        )
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_files_wanted_by_path_unmatched_prefix() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents_files().returning(|_| {
+        Ok(vec![make_test_files(
+            1,
+            &[("folder/samples2/b.mkv", 100, 0)],
+        )])
+    });
+    mock.expect_torrent_set().never();
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .set_files_wanted_by_path(1, vec![], vec!["folder/samples".to_string()])
+        .await;
+
+    match result.unwrap_err() {
+        BitTorrentError::InvalidTorrent(msg) => assert!(msg.contains("`folder/samples`")),
+        other => panic!("Expected InvalidTorrent error, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_open_file_completed() {
+    use tokio::io::AsyncReadExt;
+
+    let temp = tempfile::tempdir().unwrap();
+    std::fs::write(temp.path().join("single.bin"), b"hello torrent").unwrap();
+    let download_dir = temp.path().to_str().unwrap().to_string();
+
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_torrents_files()
+        .withf(|ids| ids == &Some(vec![1]))
+        .returning(|_| Ok(vec![make_test_files(1, &[("single.bin", 13, 13)])]));
+    mock.expect_torrents()
+        .withf(|ids| ids == &Some(vec![1]))
+        .returning(move |_| {
+            let mut torrent = make_test_torrent(1, "single.bin", "abc123");
+            torrent.download_dir = download_dir.clone();
+            Ok(vec![torrent])
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let mut reader = client.open_file(1, 0).await.unwrap();
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents).await.unwrap();
+
+    assert_eq!(contents, b"hello torrent");
+}
+
+#[tokio::test]
+async fn test_open_file_incomplete() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_torrents_files()
+        .returning(|_| Ok(vec![make_test_files(1, &[("single.bin", 1000, 500)])]));
+    mock.expect_torrents().never();
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.open_file(1, 0).await;
+
+    match result {
+        Err(BitTorrentError::ServerError(msg)) => assert!(msg.contains("not fully downloaded")),
+        Err(other) => panic!("Expected ServerError, got {other:?}"),
+        Ok(_) => panic!("Expected ServerError"),
+    }
+}
+
+#[tokio::test]
+async fn test_move_to_complete_finished() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().returning(|_| {
+        let mut torrent = make_test_torrent(1, "test_torrent", "abc123");
+        torrent.percent_done = 1.0;
+        Ok(vec![torrent])
+    });
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_torrent_set_location()
+        .withf(|ids, location, move_data| {
+            ids.as_deref() == Some(&["abc123".to_string()][..])
+                && location == "/downloads"
+                && *move_data
+        })
+        .times(1)
+        .returning(|_, _, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.move_to_complete("abc123").await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_move_to_complete_not_finished() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .returning(|_| Ok(vec![make_test_torrent(1, "test_torrent", "abc123")]));
+    mock.expect_torrent_set_location().never();
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.move_to_complete("abc123").await;
+
+    assert!(matches!(result, Err(BitTorrentError::ServerError(_))));
+}
+
+#[tokio::test]
+async fn test_set_idle_seeding_limit() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrent_set()
+        .withf(|ids, mutator| {
+            ids.as_deref() == Some(&["abc123".to_string()][..])
+                && mutator.seed_idle_limit == Some(30)
+                && mutator.seed_idle_mode == Some(1)
+        })
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .set_idle_seeding_limit(vec!["abc123".to_string()], Some(30))
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_clear_idle_seeding_limit() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrent_set()
+        .withf(|_, mutator| mutator.seed_idle_limit.is_none() && mutator.seed_idle_mode == Some(0))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .set_idle_seeding_limit(vec!["abc123".to_string()], None)
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_stop_verify_stops_torrents() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrent_stop()
+        .withf(|ids| ids.as_deref() == Some(&["abc123".to_string(), "def456".to_string()][..]))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .stop_verify(vec!["abc123".to_string(), "def456".to_string()])
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_speed_limits_set() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .withf(|ids| ids == &Some(vec![1]))
+        .returning(|_| {
+            let mut torrent = make_test_torrent(1, "test_torrent", "abc123");
+            torrent.download_limit = 500;
+            torrent.download_limited = true;
+            torrent.upload_limit = 100;
+            torrent.upload_limited = true;
+            Ok(vec![torrent])
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let limits = client.speed_limits(1).await.unwrap();
+
+    assert_eq!(limits.download, Some(500));
+    assert_eq!(limits.upload, Some(100));
+}
+
+#[tokio::test]
+async fn test_speed_limits_disabled() {
+    let mut mock = MockTransmissionOps::new();
+
+    // Limits that are configured but not enabled don't apply.
+    mock.expect_torrents().returning(|_| {
+        let mut torrent = make_test_torrent(1, "test_torrent", "abc123");
+        torrent.download_limit = 500;
+        torrent.upload_limit = 100;
+        Ok(vec![torrent])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let limits = client.speed_limits(1).await.unwrap();
+
+    assert_eq!(limits, SpeedLimits::default());
+}
+
+#[tokio::test]
+async fn test_set_honors_session_limits() {
+    let honors = Arc::new(Mutex::new(true));
+    let mut mock = MockTransmissionOps::new();
+
+    let set_honors = honors.clone();
+    mock.expect_torrent_set()
+        .withf(|ids, _| ids.as_deref() == Some(&["hash1".to_string()][..]))
+        .times(2)
+        .returning(move |_, mutator| {
+            *set_honors.lock().unwrap() = mutator.honors_session_limits.unwrap();
+            Ok(())
+        });
+    let listed_honors = honors.clone();
+    mock.expect_torrents().returning(move |_| {
+        let mut torrent = make_test_torrent(1, "torrent1", "hash1");
+        torrent.honors_session_limits = *listed_honors.lock().unwrap();
+        Ok(vec![torrent])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+
+    client
+        .set_honors_session_limits(vec!["hash1".to_string()], false)
+        .await
+        .unwrap();
+    assert!(!client.list().await.unwrap()[0].honors_session_limits);
+
+    client
+        .set_honors_session_limits(vec!["hash1".to_string()], true)
+        .await
+        .unwrap();
+    assert!(client.list().await.unwrap()[0].honors_session_limits);
+}
+
+#[tokio::test]
+async fn test_set_peer_limit() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrent_set()
+        .withf(|ids, mutator| {
+            ids.as_deref() == Some(&["hash1".to_string(), "hash2".to_string()][..])
+                && mutator.peer_limit == Some(20)
+        })
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .set_peer_limit(vec!["hash1".to_string(), "hash2".to_string()], 20)
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_peer_limit_rejects_negative() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_torrent_set().never();
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.set_peer_limit(vec!["hash1".to_string()], -1).await;
+
+    assert!(matches!(result, Err(BitTorrentError::Other(_))));
+}
+
+#[tokio::test]
+async fn test_download_dir_cached() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session()
+        .times(1)
+        .returning(|| Ok(make_test_session()));
+
+    let client = TransmissionClient::with_client(mock);
+
+    assert_eq!(client.download_dir().await.unwrap(), "/downloads");
+    assert_eq!(client.clone().download_dir().await.unwrap(), "/downloads");
+}
+
+#[tokio::test]
+async fn test_download_dir_cache_cleared() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session()
+        .times(2)
+        .returning(|| Ok(make_test_session()));
+
+    let client = TransmissionClient::with_client(mock);
+    client.download_dir().await.unwrap();
+    client.clear_download_dir_cache();
+
+    assert_eq!(client.download_dir().await.unwrap(), "/downloads");
+}
+
+/// Torrents added at 0 (unknown), 1000, 2000 and 3000 seconds after the epoch.
+fn make_torrents_added_at() -> Vec<transmission_client::Torrent> {
+    [(1, 0), (2, 1000), (3, 2000), (4, 3000)]
+        .into_iter()
+        .map(|(id, added_date)| {
+            let mut torrent = make_test_torrent(id, "torrent", &format!("hash{id}"));
+            torrent.added_date = added_date;
+            torrent
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_added_before() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .returning(|_| Ok(make_torrents_added_at()));
+
+    let client = TransmissionClient::with_client(mock);
+    let cutoff = SystemTime::UNIX_EPOCH + Duration::from_secs(2000);
+    let torrents = client.added_before(cutoff).await.unwrap();
+
+    let ids: Vec<i32> = torrents.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![2]);
+}
+
+#[tokio::test]
+async fn test_added_after() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .returning(|_| Ok(make_torrents_added_at()));
+
+    let client = TransmissionClient::with_client(mock);
+    let cutoff = SystemTime::UNIX_EPOCH + Duration::from_secs(1500);
+    let torrents = client.added_after(cutoff).await.unwrap();
+
+    let ids: Vec<i32> = torrents.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![3, 4]);
+}
+
+#[tokio::test]
+async fn test_changed_since() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().returning(|_| {
+        // (id, activity_date, edit_date)
+        Ok([
+            (1, 0, 0),
+            (2, 1000, 0),
+            (3, 2000, 0),
+            (4, 3000, 0),
+            (5, 1000, 2500),
+        ]
+        .into_iter()
+        .map(|(id, activity_date, edit_date)| {
+            let mut torrent = make_test_torrent(id, "torrent", &format!("hash{id}"));
+            torrent.added_date = 500;
+            torrent.activity_date = activity_date;
+            torrent.edit_date = edit_date;
+            torrent
+        })
+        .collect())
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    // Sub-second precision is dropped, so the torrent active at second 2000 is included.
+    let since = SystemTime::UNIX_EPOCH + Duration::from_millis(2_000_400);
+    let torrents = client.changed_since(since).await.unwrap();
+
+    let ids: Vec<i32> = torrents.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![3, 4, 5]);
+}
+
+#[tokio::test]
+async fn test_active_dir() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session()
+        .times(1)
+        .returning(|| Ok(make_test_session()));
+
+    let client = TransmissionClient::with_client(mock);
+    let mut torrent: mosaic_torrent_types::Torrent =
+        crate::conversions::TransmissionTorrentWrapper(make_test_torrent(1, "test", "abc123"))
+            .into();
+
+    assert_eq!(client.active_dir(&torrent).await.unwrap(), "/incomplete");
+
+    torrent.percent_done = 1.0;
+    assert_eq!(client.active_dir(&torrent).await.unwrap(), "/downloads");
+}
+
+#[tokio::test]
+async fn test_active_dir_without_incomplete_dir() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session().returning(|| {
+        let mut session = make_test_session();
+        session.incomplete_dir_enabled = false;
+        Ok(session)
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let torrent: mosaic_torrent_types::Torrent =
+        crate::conversions::TransmissionTorrentWrapper(make_test_torrent(1, "test", "abc123"))
+            .into();
+
+    assert_eq!(client.active_dir(&torrent).await.unwrap(), "/downloads");
+}
+
+#[tokio::test]
+async fn test_set_download_dir_creates_local_dir() {
+    let mut mock = MockTransmissionOps::new();
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("new/downloads");
+    let path = path.to_str().unwrap().to_string();
+
+    let expected = path.clone();
+    mock.expect_session_set()
+        .withf(move |mutator| mutator.download_dir.as_deref() == Some(expected.as_str()))
+        .times(1)
+        .returning(|_| Ok(()));
+    mock.expect_session().never();
+
+    let mut client = TransmissionClient::with_client(mock);
+    client.daemon_is_local = true;
+    client.set_download_dir(&path, true).await.unwrap();
+
+    assert!(std::path::Path::new(&path).is_dir());
+    assert_eq!(client.download_dir().await.unwrap(), path);
+}
+
+#[tokio::test]
+async fn test_set_download_dir_already_exists() {
+    let mut mock = MockTransmissionOps::new();
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().to_str().unwrap().to_string();
+
+    mock.expect_session_set().times(1).returning(|_| Ok(()));
+
+    let mut client = TransmissionClient::with_client(mock);
+    client.daemon_is_local = true;
+    let result = client.set_download_dir(&path, true).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_download_dir_remote_skips_create() {
+    let mut mock = MockTransmissionOps::new();
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("remote");
+    let path = path.to_str().unwrap().to_string();
+
+    mock.expect_session_set().times(1).returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    client.set_download_dir(&path, true).await.unwrap();
+
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+#[test]
+fn test_is_local() {
+    let local = |url: &str| is_local(&url::Url::parse(url).unwrap());
+
+    assert!(local("http://localhost:9091/transmission/rpc"));
+    assert!(local("http://127.0.0.1:9091/transmission/rpc"));
+    assert!(local("http://[::1]:9091/transmission/rpc"));
+    assert!(!local("http://seedbox.example.com:9091/transmission/rpc"));
+    assert!(!local("http://192.168.1.10:9091/transmission/rpc"));
+}
+
+#[tokio::test]
+async fn test_stop_in_batches() {
+    let mut mock = MockTransmissionOps::new();
+    let ids: Vec<String> = (0..1200).map(|i| format!("hash{i}")).collect();
+
+    let batch_sizes = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&batch_sizes);
+    mock.expect_torrent_stop().times(3).returning(move |ids| {
+        recorded.lock().unwrap().push(ids.unwrap().len());
+        Ok(())
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.stop(ids).await;
+
+    assert!(result.is_ok());
+    assert_eq!(*batch_sizes.lock().unwrap(), vec![500, 500, 200]);
+}
+
+#[tokio::test]
+async fn test_remove_in_batches_aggregates_errors() {
+    let mut mock = MockTransmissionOps::new();
+    let ids: Vec<String> = (0..5).map(|i| format!("hash{i}")).collect();
+
+    mock.expect_torrent_remove()
+        .times(3)
+        .returning(|ids, _| match ids.unwrap()[0].as_str() {
+            "hash2" => Ok(()),
+            first => Err(ClientError::TransmissionError(format!("failed at {first}"))),
+        });
+
+    let client = TransmissionClient::with_client(mock).with_batch_size(2);
+    let result = client.remove(ids, false).await;
+
+    match result.unwrap_err() {
+        BitTorrentError::ServerError(msg) => {
+            assert!(msg.starts_with("2 of 3 batches failed"), "{msg}");
+            assert!(msg.contains("failed at hash0"));
+            assert!(msg.contains("failed at hash4"));
+        }
+        other => panic!("Expected ServerError, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_blocklist_update() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_blocklist_update()
+        .times(1)
+        .returning(|| Ok(4242));
+
+    let client = TransmissionClient::with_client(mock);
+    let rules = client.blocklist_update().await.unwrap();
+
+    assert_eq!(rules, 4242);
+}
+
+#[tokio::test]
+async fn test_set_blocklist_enabled_with_url() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.blocklist_url.as_deref() == Some("https://example.com/blocklist.gz")
+                && mutator.blocklist_enabled == Some(true)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .set_blocklist(Some("https://example.com/blocklist.gz"), true)
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_set_blocklist_disabled() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session_set()
+        .withf(|mutator| {
+            mutator.blocklist_url.is_none() && mutator.blocklist_enabled == Some(false)
+        })
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.set_blocklist(None, false).await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_outstanding_calls_are_counted() {
+    use std::sync::{
+        OnceLock,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    let outstanding: Arc<OnceLock<Arc<AtomicUsize>>> = Arc::new(OnceLock::new());
+    let mut mock = MockTransmissionOps::new();
+
+    let during_call = outstanding.clone();
+    mock.expect_torrents().times(1).returning(move |_| {
+        assert_eq!(during_call.get().unwrap().load(Ordering::Relaxed), 1);
+        Ok(vec![])
+    });
+    mock.expect_session_stats()
+        .times(1)
+        .returning(|| Err(ClientError::TransmissionUnauthorized));
+
+    let client = TransmissionClient::with_client(mock);
+    outstanding.set(client.outstanding.clone()).unwrap();
+
+    client.list().await.unwrap();
+    assert_eq!(client.outstanding.load(Ordering::Relaxed), 0);
+
+    client.stats().await.unwrap_err();
+    assert_eq!(client.outstanding.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_peers_many_keeps_order() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_torrents_peers()
+        .times(6)
+        .returning(|ids| Ok(vec![make_test_peers(ids.unwrap()[0])]));
+    // The hashes are looked up once for all torrents.
+    mock.expect_torrents().times(1).returning(|ids| {
+        Ok(ids
+            .unwrap()
+            .into_iter()
+            .map(|id| make_test_torrent(id, "torrent", &format!("hash{id}")))
+            .collect())
+    });
+
+    // Every call takes a while, so that the peers requests overlap.
+    let ops = SlowOps::new(mock, Duration::from_millis(10));
+    let peak = ops.peak();
+    let client = TransmissionClient::with_client(ops);
+
+    let peers = client.peers_many(vec![3, 1, 4, 2, 6, 5], 2).await.unwrap();
+
+    let ids: Vec<i32> = peers.iter().map(|p| p.id).collect();
+    assert_eq!(ids, vec![3, 1, 4, 2, 6, 5]);
+    assert_eq!(peers[0].hash_string, "hash3");
+    assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_peers_many_fails_on_error() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents_peers()
+        .returning(|ids| match ids.unwrap()[0] {
+            2 => Ok(vec![]),
+            id => Ok(vec![make_test_peers(id)]),
+        });
+    mock.expect_torrents().returning(|ids| {
+        Ok(ids
+            .unwrap()
+            .into_iter()
+            .map(|id| make_test_torrent(id, "torrent", "hash"))
+            .collect())
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client.peers_many(vec![1, 2, 3], 0).await;
+
+    assert!(matches!(result, Err(BitTorrentError::InvalidTorrent(_))));
+}
+
+#[tokio::test]
+async fn test_daemon_info() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_session()
+        .times(1)
+        .returning(|| Ok(make_test_session()));
+
+    let client = TransmissionClient::with_client(mock);
+    let info = client.daemon_info().await.unwrap();
+
+    assert_eq!(info.version, "4.0.5 (a6fe2a64aa)");
+    assert_eq!(info.rpc_version, 17);
+    assert_eq!(info.config_dir, "/var/lib/transmission");
+}
+
+#[tokio::test]
+async fn test_add_from_folder() {
+    let temp = tempfile::tempdir().unwrap();
+    let folder = temp.path().join("folder");
+    std::fs::create_dir(&folder).unwrap();
+    std::fs::write(folder.join("file.txt"), "This is a test file.").unwrap();
+
+    let added_file = Arc::new(Mutex::new(None));
+    let mut mock = MockTransmissionOps::new();
+
+    let recorded = added_file.clone();
+    mock.expect_torrent_add_filename()
+        .times(1)
+        .returning(move |filename| {
+            let torrent = mosaic_torrent_types::validate_torrent_file(filename).unwrap();
+            assert_eq!(torrent.name, "folder");
+            *recorded.lock().unwrap() = Some(filename.to_string());
+            Ok(Some(make_test_torrent(1, "folder", "abc123")))
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let torrent = client
+        .add_from_folder(folder.to_str().unwrap(), Some("udp://tracker.example:1337"))
+        .await
+        .unwrap();
+
+    assert_eq!(torrent.name, "folder");
+    let added_file = added_file.lock().unwrap().clone().unwrap();
+    assert!(!std::path::Path::new(&added_file).exists());
+}
+
+#[tokio::test]
+async fn test_add_from_missing_folder() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_torrent_add_filename().never();
+
+    let client = TransmissionClient::with_client(mock);
+    let result = client
+        .add_from_folder("/nonexistent/mosaic/folder", None)
+        .await;
+
+    assert!(matches!(result, Err(BitTorrentError::InvalidTorrent(_))));
+}
+
+#[tokio::test]
+async fn test_get_many() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents()
+        .withf(|ids| ids.is_none())
+        .times(1)
+        .returning(|_| {
+            Ok(vec![
+                make_test_torrent(1, "first", "abc123"),
+                make_test_torrent(2, "second", "def456"),
+                make_test_torrent(3, "untracked", "987fed"),
+            ])
+        });
+
+    let client = TransmissionClient::with_client(mock);
+    let torrents = client
+        .get_many(vec![
+            "DEF456".to_string(),
+            "missing".to_string(),
+            "abc123".to_string(),
+        ])
+        .await
+        .unwrap();
+
+    let ids: Vec<i32> = torrents.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![2, 1]);
+}
+
+#[tokio::test]
+async fn test_find_by_info_hash() {
+    let mut mock = MockTransmissionOps::new();
+
+    mock.expect_torrents().returning(|_| {
+        Ok(vec![
+            make_test_torrent(1, "first", "abc123"),
+            make_test_torrent(2, "renamed", "def456"),
+        ])
+    });
+
+    let client = TransmissionClient::with_client(mock);
+    let found = client.find_by_info_hash("DEF456").await.unwrap();
+    assert_eq!(found.map(|t| t.id), Some(2));
+
+    let missing = client.find_by_info_hash("fff000").await.unwrap();
+    assert!(missing.is_none());
+}
+
+#[test]
+fn test_error_mapping_unauthorized() {
+    let err = map_client_error(ClientError::TransmissionUnauthorized);
+    assert!(matches!(err, BitTorrentError::Unauthorized));
+}
+
+#[test]
+fn test_error_mapping_server_error() {
+    let err = map_client_error(ClientError::TransmissionError("test error".to_string()));
+    match err {
+        BitTorrentError::ServerError(msg) => assert_eq!(msg, "test error"),
+        _ => panic!("Expected ServerError"),
+    }
+}
+
+#[tokio::test]
+async fn test_error_mapping_connect_failure() {
+    let err = map_client_error(make_network_error().await);
+    assert!(matches!(err, BitTorrentError::Network(_)));
+}
+
+#[tokio::test]
+async fn test_error_mapping_request_timeout() {
+    let err = map_client_error(make_request_timeout_error().await);
+    assert!(matches!(err, BitTorrentError::Timeout(_)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_request_timeout_maps_to_timeout() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_torrents().returning(|_| Ok(vec![]));
+
+    let client = TransmissionClient::with_client(SlowOps::new(mock, Duration::from_secs(10)))
+        .with_request_timeout(Duration::from_secs(1));
+    let result = client.list().await;
+
+    assert!(matches!(result, Err(BitTorrentError::Timeout(_))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_request_timeout_allows_slow_responses_within_limit() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_torrents()
+        .returning(|_| Ok(vec![make_test_torrent(1, "test", "abc123")]));
+
+    let client = TransmissionClient::with_client(SlowOps::new(mock, Duration::from_secs(10)))
+        .with_request_timeout(Duration::from_secs(30));
+    let torrents = client.list().await.unwrap();
+
+    assert_eq!(torrents.len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_connect_timeout_maps_to_network() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_session_set().returning(|_| Ok(()));
+
+    let result = TransmissionClient::try_with_client_timeouts(
+        SlowOps::new(mock, Duration::from_secs(10)),
+        3,
+        Some(Duration::from_secs(1)),
+        None,
+    )
+    .await;
+
+    assert!(matches!(result, Err(BitTorrentError::Network(_))));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_connect_timeout_only_bounds_first_request() {
+    let mut mock = MockTransmissionOps::new();
+    mock.expect_session().returning(|| Ok(make_test_session()));
+    mock.expect_session_set().returning(|_| Ok(()));
+
+    // Every call takes 10s: the version check is within the connect timeout, while applying
+    // the session settings exceeds the request timeout.
+    let result = TransmissionClient::try_with_client_timeouts(
+        SlowOps::new(mock, Duration::from_secs(10)),
+        3,
+        Some(Duration::from_secs(30)),
+        Some(Duration::from_secs(1)),
+    )
+    .await;
+
+    assert!(matches!(result, Err(BitTorrentError::Timeout(_))));
+}
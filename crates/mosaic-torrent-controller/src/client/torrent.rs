@@ -0,0 +1,70 @@
+//! Per-torrent settings of the Transmission daemon.
+
+use transmission_client::TorrentMutator;
+
+use mosaic_torrent_types::{BitTorrent, BitTorrentError, SpeedLimits};
+
+use super::TransmissionClient;
+use crate::logging::debug;
+use crate::ops::TransmissionOps;
+
+/// `seedIdleMode` following the session-wide idle limit.
+const SEED_IDLE_MODE_GLOBAL: i32 = 0;
+
+/// `seedIdleMode` using the torrent's own `seedIdleLimit`.
+const SEED_IDLE_MODE_SINGLE: i32 = 1;
+
+#[allow(private_bounds)]
+impl<T: TransmissionOps> TransmissionClient<T> {
+    /// Stop seeding the given torrents (by hash) once they have been idle for `minutes`.
+    ///
+    /// `None` clears the per-torrent limit, so the torrents follow the session's idle seeding
+    /// setting again, which is unlimited unless configured otherwise.
+    pub async fn set_idle_seeding_limit(
+        &self,
+        ids: Vec<String>,
+        minutes: Option<u32>,
+    ) -> Result<(), BitTorrentError> {
+        debug!("Setting idle seeding limit of torrents {ids:?} to {minutes:?} minutes");
+        let mutator = match minutes {
+            Some(minutes) => TorrentMutator {
+                seed_idle_limit: Some(i32::try_from(minutes).map_err(|_| {
+                    BitTorrentError::Other(format!("idle seeding limit too large: {minutes}"))
+                })?),
+                seed_idle_mode: Some(SEED_IDLE_MODE_SINGLE),
+                ..Default::default()
+            },
+            None => TorrentMutator {
+                seed_idle_mode: Some(SEED_IDLE_MODE_GLOBAL),
+                ..Default::default()
+            },
+        };
+        self.call(|ops| ops.torrent_set(Some(ids), mutator)).await?;
+        debug!("Idle seeding limit set");
+        Ok(())
+    }
+
+    /// Abort the verification of the given torrents (by hash), e.g. a recheck of a large
+    /// torrent started by mistake.
+    ///
+    /// Transmission has no call to cancel a verification on its own, but stopping a torrent
+    /// aborts a running verification and takes it out of the verification queue. This is the
+    /// same as [`BitTorrent::stop`]: the torrents stay stopped afterwards, and their local data
+    /// is verified from the start again the next time a verification is requested.
+    pub async fn stop_verify(&self, ids: Vec<String>) -> Result<(), BitTorrentError> {
+        debug!("Aborting verification of torrents {ids:?}");
+        self.stop(ids).await
+    }
+
+    /// The speed limits of torrent `id` as set on the daemon, with disabled limits as `None`.
+    pub async fn speed_limits(&self, id: i32) -> Result<SpeedLimits, BitTorrentError> {
+        debug!("Getting speed limits of torrent {id}");
+        let torrent = self.torrent_by_id(id).await?;
+        let limits = SpeedLimits {
+            download: torrent.download_limited.then_some(torrent.download_limit),
+            upload: torrent.upload_limited.then_some(torrent.upload_limit),
+        };
+        debug!("Speed limits of torrent {id}: {limits:?}");
+        Ok(limits)
+    }
+}
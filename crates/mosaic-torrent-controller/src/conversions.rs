@@ -2,6 +2,9 @@
 //!
 //! These newtype wrappers exist to satisfy the orphan rule since both the source
 //! and target types are defined outside this crate.
+//!
+//! Fields are copied without numeric casts: every target field is at least as wide as its
+//! source, with byte counts as `i64`, so no value can wrap around during conversion.
 
 use mosaic_torrent_types::{Peers, SessionStats, StatsDetails, Torrent};
 use transmission_client::{
@@ -21,9 +24,10 @@ pub(crate) struct TransmissionStatsDetailsWrapper(pub(crate) TransmissionStatsDe
 #[derive(Debug)]
 pub(crate) struct TransmissionTorrentWrapper(pub(crate) TransmissionTorrent);
 
-/// Wrapper for converting `TorrentPeers` to `Peers`.
+/// Wrapper for converting `TorrentPeers` to `Peers`, along with the hash of the torrent they
+/// were fetched for, as `TorrentPeers` only carries the numeric id.
 #[derive(Debug)]
-pub(crate) struct TransmissionTorrentPeersWrapper(pub(crate) TorrentPeers);
+pub(crate) struct TransmissionTorrentPeersWrapper(pub(crate) TorrentPeers, pub(crate) String);
 
 impl From<TransmissionSessionStatsWrapper> for SessionStats {
     fn from(wrapper: TransmissionSessionStatsWrapper) -> Self {
@@ -64,20 +68,30 @@ impl From<TransmissionTorrentWrapper> for Torrent {
             comment: value.comment,
             creator: value.creator,
             date_created: value.date_created,
+            desired_available: value.desired_available,
             download_dir: value.download_dir,
             download_limit: value.download_limit,
             download_limited: value.download_limited,
+            edit_date: value.edit_date,
             eta: value.eta,
             eta_idle: value.eta_idle,
             hash_string: value.hash_string,
             have_unchecked: value.have_unchecked,
             have_valid: value.have_valid,
+            honors_session_limits: value.honors_session_limits,
             is_finished: value.is_finished,
             is_private: value.is_private,
             is_stalled: value.is_stalled,
+            left_until_done: value.left_until_done,
+            metadata_percent_complete: value.metadata_percent_complete,
             name: value.name,
             percent_done: value.percent_done,
             queue_position: value.queue_position,
+            rate_download: value.rate_download,
+            rate_upload: value.rate_upload,
+            seed_idle_limit: value.seed_idle_limit,
+            seed_idle_mode: value.seed_idle_mode,
+            size_when_done: value.size_when_done,
             start_date: value.start_date,
             status: value.status,
             torrent_file: value.torrent_file,
@@ -88,9 +102,10 @@ impl From<TransmissionTorrentWrapper> for Torrent {
 
 impl From<TransmissionTorrentPeersWrapper> for Peers {
     fn from(wrapper: TransmissionTorrentPeersWrapper) -> Self {
-        let value = wrapper.0;
+        let (value, hash_string) = (wrapper.0, wrapper.1);
         Self {
             id: value.id,
+            hash_string,
             peer_limit: value.peer_limit,
             peers_connected: value.peers_connected,
             peers_getting_from_us: value.peers_getting_from_us,
@@ -116,14 +131,31 @@ mod tests {
         assert_eq!(torrent.hash_string, "deadbeef");
         assert_eq!(torrent.percent_done, 0.5);
         assert_eq!(torrent.download_dir, "/downloads");
+        assert_eq!(torrent.size_when_done, 1000);
+        assert_eq!(torrent.left_until_done, 500);
+        assert_eq!(torrent.bytes_remaining(), 500);
+    }
+
+    #[test]
+    fn test_torrent_availability_conversion() {
+        let mut transmission_torrent = make_test_torrent(1, "Partial", "deadbeef");
+        transmission_torrent.desired_available = 300;
+        transmission_torrent.left_until_done = 600;
+        let torrent: Torrent = TransmissionTorrentWrapper(transmission_torrent).into();
+
+        assert_eq!(torrent.desired_available, 300);
+        assert_eq!(torrent.left_until_done, 600);
+        assert_eq!(torrent.availability_fraction(), 0.5);
     }
 
     #[test]
     fn test_peers_conversion() {
         let transmission_peers = make_test_peers(10);
-        let peers: Peers = TransmissionTorrentPeersWrapper(transmission_peers).into();
+        let peers: Peers =
+            TransmissionTorrentPeersWrapper(transmission_peers, "deadbeef".to_string()).into();
 
         assert_eq!(peers.id, 10);
+        assert_eq!(peers.hash_string, "deadbeef");
         assert_eq!(peers.peer_limit, 100);
         assert_eq!(peers.peers_connected, 5);
         assert_eq!(peers.peers_getting_from_us, 2);
@@ -142,4 +174,26 @@ mod tests {
         assert_eq!(stats.cumulative_stats.session_count, 10);
         assert_eq!(stats.current_stats.downloaded_bytes, 100);
     }
+
+    #[test]
+    fn test_large_values_do_not_wrap() {
+        let five_gib = 5 * 1024 * 1024 * 1024_i64;
+        let mut transmission_torrent = make_test_torrent(1, "Large", "deadbeef");
+        transmission_torrent.total_size = five_gib;
+        transmission_torrent.have_valid = five_gib;
+        transmission_torrent.desired_available = five_gib;
+        transmission_torrent.left_until_done = five_gib;
+        let torrent: Torrent = TransmissionTorrentWrapper(transmission_torrent).into();
+
+        assert_eq!(torrent.total_size, five_gib);
+        assert_eq!(torrent.have_valid, five_gib);
+        assert_eq!(torrent.desired_available, five_gib);
+        assert_eq!(torrent.left_until_done, five_gib);
+
+        let mut transmission_stats = make_test_stats();
+        transmission_stats.cumulative_stats.downloaded_bytes = i64::MAX;
+        let stats: SessionStats = TransmissionSessionStatsWrapper(transmission_stats).into();
+
+        assert_eq!(stats.cumulative_stats.downloaded_bytes, i64::MAX);
+    }
 }
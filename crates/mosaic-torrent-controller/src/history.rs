@@ -0,0 +1,169 @@
+//! In-memory history and peaks of session transfer speeds.
+
+use std::{collections::VecDeque, time::SystemTime};
+
+use mosaic_torrent_types::SessionStats;
+
+/// A ring buffer of the most recent download and upload speeds, e.g. for a bandwidth graph.
+///
+/// Feed it the results of [`BitTorrent::stats`](mosaic_torrent_types::BitTorrent::stats) as they
+/// are polled. Once full, every new sample replaces the oldest one.
+#[derive(Debug, Clone)]
+pub struct SpeedHistory {
+    capacity: usize,
+    samples: VecDeque<(SystemTime, i32, i32)>,
+}
+
+impl SpeedHistory {
+    /// Create a history keeping the last `capacity` samples. A capacity of `0` is treated as `1`.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Record the current download and upload speeds of `stats`, timestamped now.
+    pub fn push(&mut self, stats: &SessionStats) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples
+            .push_back((SystemTime::now(), stats.download_speed, stats.upload_speed));
+    }
+
+    /// The last `n` samples as `(time, download, upload)` with speeds in bytes per second,
+    /// oldest first. Returns fewer if fewer have been recorded.
+    pub fn recent(&self, n: usize) -> Vec<(SystemTime, i32, i32)> {
+        let skip = self.samples.len().saturating_sub(n);
+        self.samples.iter().skip(skip).copied().collect()
+    }
+}
+
+/// The highest download and upload speeds seen, with when they were seen, e.g. for capacity
+/// planning. Transmission doesn't keep these, so they only cover the samples pushed here.
+///
+/// Feed it the results of [`BitTorrent::stats`](mosaic_torrent_types::BitTorrent::stats) as they
+/// are polled. On a tie, the earlier sample is kept.
+#[derive(Debug, Clone, Default)]
+pub struct RatePeaks {
+    download: Option<(i32, SystemTime)>,
+    upload: Option<(i32, SystemTime)>,
+}
+
+impl RatePeaks {
+    /// Create a tracker with no samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the current download and upload speeds of `stats`, timestamped now.
+    pub fn push(&mut self, stats: &SessionStats) {
+        self.push_at(stats, SystemTime::now());
+    }
+
+    /// Record the current download and upload speeds of `stats`, as sampled at `at`.
+    pub fn push_at(&mut self, stats: &SessionStats, at: SystemTime) {
+        for (peak, speed) in [
+            (&mut self.download, stats.download_speed),
+            (&mut self.upload, stats.upload_speed),
+        ] {
+            if peak.is_none_or(|(max, _)| speed > max) {
+                *peak = Some((speed, at));
+            }
+        }
+    }
+
+    /// The highest download speed seen in bytes per second and when, or `None` without samples.
+    pub fn peak_download(&self) -> Option<(i32, SystemTime)> {
+        self.download
+    }
+
+    /// The highest upload speed seen in bytes per second and when, or `None` without samples.
+    pub fn peak_upload(&self) -> Option<(i32, SystemTime)> {
+        self.upload
+    }
+
+    /// Forget all samples, e.g. to start a new measurement period.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::TransmissionSessionStatsWrapper;
+    use crate::testutil::make_test_stats;
+
+    fn stats(download_speed: i32, upload_speed: i32) -> SessionStats {
+        let mut stats = make_test_stats();
+        stats.download_speed = download_speed;
+        stats.upload_speed = upload_speed;
+        TransmissionSessionStatsWrapper(stats).into()
+    }
+
+    fn speeds(samples: &[(SystemTime, i32, i32)]) -> Vec<(i32, i32)> {
+        samples.iter().map(|&(_, down, up)| (down, up)).collect()
+    }
+
+    #[test]
+    fn recent_returns_latest_samples_oldest_first() {
+        let mut history = SpeedHistory::new(5);
+        for i in 1..=3 {
+            history.push(&stats(i * 100, i * 10));
+        }
+
+        assert_eq!(speeds(&history.recent(2)), vec![(200, 20), (300, 30)]);
+        assert_eq!(
+            speeds(&history.recent(10)),
+            vec![(100, 10), (200, 20), (300, 30)]
+        );
+        assert!(history.recent(0).is_empty());
+
+        let samples = history.recent(3);
+        assert!(samples.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    }
+
+    #[test]
+    fn push_past_capacity_drops_oldest() {
+        let mut history = SpeedHistory::new(3);
+        for i in 1..=5 {
+            history.push(&stats(i, 0));
+        }
+
+        assert_eq!(speeds(&history.recent(5)), vec![(3, 0), (4, 0), (5, 0)]);
+    }
+
+    #[test]
+    fn zero_capacity_keeps_last_sample() {
+        let mut history = SpeedHistory::new(0);
+        history.push(&stats(1, 1));
+        history.push(&stats(2, 2));
+
+        assert_eq!(speeds(&history.recent(5)), vec![(2, 2)]);
+    }
+
+    #[test]
+    fn rate_peaks_track_max_and_time() {
+        let at = |secs| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        let mut peaks = RatePeaks::new();
+        assert_eq!(peaks.peak_download(), None);
+        assert_eq!(peaks.peak_upload(), None);
+
+        peaks.push_at(&stats(100, 50), at(1));
+        peaks.push_at(&stats(300, 20), at(2));
+        peaks.push_at(&stats(200, 80), at(3));
+        peaks.push_at(&stats(300, 80), at(4));
+
+        assert_eq!(peaks.peak_download(), Some((300, at(2))));
+        assert_eq!(peaks.peak_upload(), Some((80, at(3))));
+
+        peaks.reset();
+        assert_eq!(peaks.peak_download(), None);
+        peaks.push_at(&stats(10, 0), at(5));
+        assert_eq!(peaks.peak_download(), Some((10, at(5))));
+        assert_eq!(peaks.peak_upload(), Some((0, at(5))));
+    }
+}
@@ -6,6 +6,11 @@
 //! This crate provides a [`TransmissionClient`] that implements the [`mosaic_torrent_types::BitTorrent`] trait
 //! from `mosaic_torrent_types`, allowing you to manage torrents through the Transmission daemon.
 //!
+//! ## Features
+//!
+//! - `tracing` (enabled by default): logs the RPC calls at debug level using `tracing`. Disable
+//!   default features to drop the `tracing` dependency, e.g. for minimal binaries.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -26,11 +31,18 @@
 //! }
 //! ```
 
+mod cached;
 mod client;
 mod conversions;
+mod history;
+mod logging;
 mod ops;
+mod poll;
 
 #[cfg(test)]
 mod testutil;
 
+pub use cached::CachedTransmissionClient;
 pub use client::TransmissionClient;
+pub use history::{RatePeaks, SpeedHistory};
+pub use poll::PollStrategy;
@@ -0,0 +1,19 @@
+//! Logging macros, backed by `tracing` when the `tracing` feature is enabled.
+//!
+//! Without the feature, the macros expand to code that is never run, so their arguments are still
+//! type-checked and count as used, but nothing is logged and `tracing` is not a dependency.
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::debug;
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = ::std::format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use debug;
@@ -4,8 +4,9 @@
 //! transmission client, enabling mocking in tests.
 
 use transmission_client::{
-    Client, ClientError, SessionStats as TransmissionSessionStats, Torrent as TransmissionTorrent,
-    TorrentPeers,
+    Client, ClientError, Session as TransmissionSession, SessionMutator,
+    SessionStats as TransmissionSessionStats, Torrent as TransmissionTorrent, TorrentFiles,
+    TorrentMutator, TorrentPeers,
 };
 
 /// Internal trait that abstracts the transmission client operations.
@@ -18,18 +19,37 @@ pub(crate) trait TransmissionOps {
         filename: &str,
     ) -> Result<Option<TransmissionTorrent>, ClientError>;
     async fn torrent_stop(&self, ids: Option<Vec<String>>) -> Result<(), ClientError>;
+    async fn torrent_verify(&self, ids: Option<Vec<String>>) -> Result<(), ClientError>;
     async fn torrents(
         &self,
         ids: Option<Vec<i32>>,
     ) -> Result<Vec<TransmissionTorrent>, ClientError>;
     async fn torrents_peers(&self, ids: Option<Vec<i32>>)
     -> Result<Vec<TorrentPeers>, ClientError>;
+    async fn torrents_files(&self, ids: Option<Vec<i32>>)
+    -> Result<Vec<TorrentFiles>, ClientError>;
     async fn torrent_remove(
         &self,
         ids: Option<Vec<String>>,
         delete_local_data: bool,
     ) -> Result<(), ClientError>;
     async fn session_stats(&self) -> Result<TransmissionSessionStats, ClientError>;
+    async fn session(&self) -> Result<TransmissionSession, ClientError>;
+    async fn session_set(&self, mutator: SessionMutator) -> Result<(), ClientError>;
+    async fn port_test(&self) -> Result<bool, ClientError>;
+    async fn free_space(&self, path: String) -> Result<i64, ClientError>;
+    async fn torrent_set_location(
+        &self,
+        ids: Option<Vec<String>>,
+        location: String,
+        move_data: bool,
+    ) -> Result<(), ClientError>;
+    async fn torrent_set(
+        &self,
+        ids: Option<Vec<String>>,
+        mutator: TorrentMutator,
+    ) -> Result<(), ClientError>;
+    async fn blocklist_update(&self) -> Result<i32, ClientError>;
 }
 
 impl TransmissionOps for Client {
@@ -44,6 +64,10 @@ impl TransmissionOps for Client {
         Client::torrent_stop(self, ids).await
     }
 
+    async fn torrent_verify(&self, ids: Option<Vec<String>>) -> Result<(), ClientError> {
+        Client::torrent_verify(self, ids).await
+    }
+
     async fn torrents(
         &self,
         ids: Option<Vec<i32>>,
@@ -58,6 +82,13 @@ impl TransmissionOps for Client {
         Client::torrents_peers(self, ids).await
     }
 
+    async fn torrents_files(
+        &self,
+        ids: Option<Vec<i32>>,
+    ) -> Result<Vec<TorrentFiles>, ClientError> {
+        Client::torrents_files(self, ids).await
+    }
+
     async fn torrent_remove(
         &self,
         ids: Option<Vec<String>>,
@@ -69,4 +100,43 @@ impl TransmissionOps for Client {
     async fn session_stats(&self) -> Result<TransmissionSessionStats, ClientError> {
         Client::session_stats(self).await
     }
+
+    async fn session(&self) -> Result<TransmissionSession, ClientError> {
+        Client::session(self).await
+    }
+
+    async fn session_set(&self, mutator: SessionMutator) -> Result<(), ClientError> {
+        Client::session_set(self, mutator).await
+    }
+
+    async fn port_test(&self) -> Result<bool, ClientError> {
+        Client::port_test(self).await
+    }
+
+    async fn free_space(&self, path: String) -> Result<i64, ClientError> {
+        Client::free_space(self, path)
+            .await
+            .map(|free_space| free_space.size_bytes)
+    }
+
+    async fn torrent_set_location(
+        &self,
+        ids: Option<Vec<String>>,
+        location: String,
+        move_data: bool,
+    ) -> Result<(), ClientError> {
+        Client::torrent_set_location(self, ids, location, move_data).await
+    }
+
+    async fn torrent_set(
+        &self,
+        ids: Option<Vec<String>>,
+        mutator: TorrentMutator,
+    ) -> Result<(), ClientError> {
+        Client::torrent_set(self, ids, mutator).await
+    }
+
+    async fn blocklist_update(&self) -> Result<i32, ClientError> {
+        Client::blocklist_update(self).await
+    }
 }
@@ -0,0 +1,64 @@
+//! Polling intervals for helpers that wait on the daemon.
+
+use std::time::Duration;
+
+/// How long to wait between consecutive polls, e.g. in
+/// [`TransmissionClient::wait_until_complete`](crate::TransmissionClient::wait_until_complete).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollStrategy {
+    /// Always wait the same interval.
+    Fixed(Duration),
+    /// Start with `base` and double the interval after every poll, up to `max`. This reacts
+    /// quickly early on without hammering the daemon during long waits.
+    Exponential {
+        /// The interval before the second poll.
+        base: Duration,
+        /// The longest interval between polls.
+        max: Duration,
+    },
+}
+
+impl PollStrategy {
+    /// The interval to wait after the poll with the given zero-based index.
+    pub fn interval(&self, poll: u32) -> Duration {
+        match *self {
+            Self::Fixed(interval) => interval,
+            Self::Exponential { base, max } => base
+                .checked_mul(2u32.saturating_pow(poll))
+                .map_or(max, |interval| interval.min(max)),
+        }
+    }
+}
+
+impl From<Duration> for PollStrategy {
+    fn from(interval: Duration) -> Self {
+        Self::Fixed(interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_interval() {
+        let strategy = PollStrategy::Fixed(Duration::from_millis(250));
+
+        assert_eq!(strategy.interval(0), Duration::from_millis(250));
+        assert_eq!(strategy.interval(10), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn exponential_interval_grows_and_is_capped() {
+        let strategy = PollStrategy::Exponential {
+            base: Duration::from_millis(100),
+            max: Duration::from_secs(1),
+        };
+
+        let intervals: Vec<u64> = (0..6)
+            .map(|poll| strategy.interval(poll).as_millis() as u64)
+            .collect();
+        assert_eq!(intervals, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(strategy.interval(u32::MAX), Duration::from_secs(1));
+    }
+}
@@ -1,10 +1,23 @@
 //! Shared test utilities and fixtures.
 
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
 use transmission_client::{
-    SessionStats as TransmissionSessionStats, StatsDetails as TransmissionStatsDetails,
-    Torrent as TransmissionTorrent, TorrentPeers,
+    ClientError, File as TransmissionFile, FileStat as TransmissionFileStat,
+    Session as TransmissionSession, SessionMutator, SessionStats as TransmissionSessionStats,
+    StatsDetails as TransmissionStatsDetails, Torrent as TransmissionTorrent, TorrentFiles,
+    TorrentMutator, TorrentPeers,
 };
 
+use crate::ops::{MockTransmissionOps, TransmissionOps};
+
 pub(crate) fn make_test_torrent(id: i32, name: &str, hash: &str) -> TransmissionTorrent {
     TransmissionTorrent {
         id,
@@ -33,7 +46,7 @@ pub(crate) fn make_test_torrent(id: i32, name: &str, hash: &str) -> Transmission
         is_finished: false,
         is_private: false,
         is_stalled: false,
-        left_until_done: 0,
+        left_until_done: 500,
         magnet_link: String::new(),
         manual_announce_time: 0,
         metadata_percent_complete: 1.0,
@@ -65,6 +78,22 @@ pub(crate) fn make_test_torrent(id: i32, name: &str, hash: &str) -> Transmission
     }
 }
 
+/// The torrent in a `torrent-add` reply, which only carries the id, name and hash.
+pub(crate) fn make_added_torrent(id: i32, name: &str, hash: &str) -> TransmissionTorrent {
+    TransmissionTorrent {
+        download_dir: String::new(),
+        honors_session_limits: false,
+        left_until_done: 0,
+        metadata_percent_complete: 0.0,
+        percent_done: 0.0,
+        piece_count: 0,
+        piece_size: 0,
+        status: 0,
+        torrent_file: String::new(),
+        ..make_test_torrent(id, name, hash)
+    }
+}
+
 pub(crate) fn make_test_peers(id: i32) -> TorrentPeers {
     TorrentPeers {
         id,
@@ -77,6 +106,31 @@ pub(crate) fn make_test_peers(id: i32) -> TorrentPeers {
     }
 }
 
+/// Builds the file list of a torrent from `(name, length, bytes_completed)` entries.
+pub(crate) fn make_test_files(id: i32, files: &[(&str, i64, i64)]) -> TorrentFiles {
+    TorrentFiles {
+        id,
+        files: files
+            .iter()
+            .map(|&(name, length, bytes_completed)| TransmissionFile {
+                name: name.to_string(),
+                length,
+                bytes_completed,
+            })
+            .collect(),
+        file_stats: files
+            .iter()
+            .map(|&(_, _, bytes_completed)| TransmissionFileStat {
+                bytes_completed,
+                wanted: true,
+                priority: 0,
+            })
+            .collect(),
+        wanted: vec![true; files.len()],
+        priorities: vec![0; files.len()],
+    }
+}
+
 pub(crate) fn make_test_stats() -> TransmissionSessionStats {
     TransmissionSessionStats {
         active_torrent_count: 1,
@@ -100,3 +154,172 @@ pub(crate) fn make_test_stats() -> TransmissionSessionStats {
         upload_speed: 500,
     }
 }
+
+pub(crate) fn make_test_session() -> TransmissionSession {
+    TransmissionSession {
+        config_dir: "/var/lib/transmission".to_string(),
+        download_dir: "/downloads".to_string(),
+        incomplete_dir: "/incomplete".to_string(),
+        incomplete_dir_enabled: true,
+        peer_port: 51413,
+        port_forwarding_enabled: false,
+        rpc_version: 17,
+        rpc_version_minimum: 14,
+        version: "4.0.5 (a6fe2a64aa)".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Produces a genuine network error by connecting to a port nothing listens on.
+pub(crate) async fn make_network_error() -> ClientError {
+    let err = reqwest::get("http://127.0.0.1:1/transmission/rpc")
+        .await
+        .expect_err("nothing should listen on port 1");
+    ClientError::NetworkError(err)
+}
+
+/// Produces a genuine request timeout by connecting to a server that never responds.
+pub(crate) async fn make_request_timeout_error() -> ClientError {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Accept the connection but never answer, keeping it open until the test ends.
+    tokio::spawn(async move {
+        let (_socket, _) = listener.accept().await.unwrap();
+        std::future::pending::<()>().await;
+    });
+
+    let err = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(50))
+        .build()
+        .unwrap()
+        .get(format!("http://{addr}/transmission/rpc"))
+        .send()
+        .await
+        .expect_err("the server never responds");
+    ClientError::NetworkError(err)
+}
+
+/// Wraps a [`MockTransmissionOps`], whose calls complete immediately, so that every call takes
+/// `delay` before reaching the mock. This lets calls overlap, and records the most calls ever in
+/// flight at once in `peak`. Use with a paused clock to keep tests fast.
+pub(crate) struct SlowOps {
+    inner: MockTransmissionOps,
+    delay: Duration,
+    in_flight: AtomicUsize,
+    peak: Arc<AtomicUsize>,
+}
+
+impl SlowOps {
+    pub(crate) fn new(inner: MockTransmissionOps, delay: Duration) -> Self {
+        Self {
+            inner,
+            delay,
+            in_flight: AtomicUsize::new(0),
+            peak: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// The most calls that were in flight at once, shared with the wrapper.
+    pub(crate) fn peak(&self) -> Arc<AtomicUsize> {
+        self.peak.clone()
+    }
+
+    async fn slow<F: Future>(&self, call: impl FnOnce() -> F) -> F::Output {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        let output = call().await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        output
+    }
+}
+
+impl TransmissionOps for SlowOps {
+    async fn torrent_add_filename(
+        &self,
+        filename: &str,
+    ) -> Result<Option<TransmissionTorrent>, ClientError> {
+        self.slow(|| self.inner.torrent_add_filename(filename))
+            .await
+    }
+
+    async fn torrent_stop(&self, ids: Option<Vec<String>>) -> Result<(), ClientError> {
+        self.slow(|| self.inner.torrent_stop(ids)).await
+    }
+
+    async fn torrent_verify(&self, ids: Option<Vec<String>>) -> Result<(), ClientError> {
+        self.slow(|| self.inner.torrent_verify(ids)).await
+    }
+
+    async fn torrents(
+        &self,
+        ids: Option<Vec<i32>>,
+    ) -> Result<Vec<TransmissionTorrent>, ClientError> {
+        self.slow(|| self.inner.torrents(ids)).await
+    }
+
+    async fn torrents_peers(
+        &self,
+        ids: Option<Vec<i32>>,
+    ) -> Result<Vec<TorrentPeers>, ClientError> {
+        self.slow(|| self.inner.torrents_peers(ids)).await
+    }
+
+    async fn torrents_files(
+        &self,
+        ids: Option<Vec<i32>>,
+    ) -> Result<Vec<TorrentFiles>, ClientError> {
+        self.slow(|| self.inner.torrents_files(ids)).await
+    }
+
+    async fn torrent_remove(
+        &self,
+        ids: Option<Vec<String>>,
+        delete_local_data: bool,
+    ) -> Result<(), ClientError> {
+        self.slow(|| self.inner.torrent_remove(ids, delete_local_data))
+            .await
+    }
+
+    async fn session_stats(&self) -> Result<TransmissionSessionStats, ClientError> {
+        self.slow(|| self.inner.session_stats()).await
+    }
+
+    async fn session(&self) -> Result<TransmissionSession, ClientError> {
+        self.slow(|| self.inner.session()).await
+    }
+
+    async fn session_set(&self, mutator: SessionMutator) -> Result<(), ClientError> {
+        self.slow(|| self.inner.session_set(mutator)).await
+    }
+
+    async fn port_test(&self) -> Result<bool, ClientError> {
+        self.slow(|| self.inner.port_test()).await
+    }
+
+    async fn free_space(&self, path: String) -> Result<i64, ClientError> {
+        self.slow(|| self.inner.free_space(path)).await
+    }
+
+    async fn torrent_set_location(
+        &self,
+        ids: Option<Vec<String>>,
+        location: String,
+        move_data: bool,
+    ) -> Result<(), ClientError> {
+        self.slow(|| self.inner.torrent_set_location(ids, location, move_data))
+            .await
+    }
+
+    async fn torrent_set(
+        &self,
+        ids: Option<Vec<String>>,
+        mutator: TorrentMutator,
+    ) -> Result<(), ClientError> {
+        self.slow(|| self.inner.torrent_set(ids, mutator)).await
+    }
+
+    async fn blocklist_update(&self) -> Result<i32, ClientError> {
+        self.slow(|| self.inner.blocklist_update()).await
+    }
+}
@@ -0,0 +1,661 @@
+//! Torrent file creation with configurable file selection.
+//!
+//! [`TorrentBuilder`](lava_torrent::torrent::v1::TorrentBuilder) only accepts a path and hashes
+//! everything below it. To leave files out, the folder is walked and filtered here instead, and
+//! the torrent is built from the resulting file list.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use lava_torrent::{
+    bencode::BencodeElem,
+    torrent::v1::{File as MetainfoFile, Torrent as Metainfo},
+};
+use sha1::{Digest, Sha1};
+
+use crate::BitTorrentError;
+
+/// Piece length used when creating torrents (1 MiB).
+pub(crate) const DEFAULT_PIECE_LENGTH: i64 = 1048576;
+
+/// Size of the buffer used when reading files for hashing.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+const KIB: i64 = 1024;
+const MIB: i64 = 1024 * KIB;
+const GIB: i64 = 1024 * MIB;
+
+/// The piece lengths picked by [`PieceSelection::Auto`]: the first entry whose total size bound
+/// exceeds the torrent's size applies, larger torrents use [`AUTO_MAX_PIECE_LENGTH`].
+const AUTO_PIECE_LENGTHS: [(i64, i64); 4] = [
+    (512 * MIB, 256 * KIB),
+    (2 * GIB, 512 * KIB),
+    (8 * GIB, MIB),
+    (32 * GIB, 2 * MIB),
+];
+
+/// The piece length picked by [`PieceSelection::Auto`] for torrents of 32 GiB and more.
+const AUTO_MAX_PIECE_LENGTH: i64 = 4 * MIB;
+
+/// How the piece length of a created torrent is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceSelection {
+    /// Use the given piece length in bytes, which must be a power of two.
+    Fixed(i64),
+    /// Pick the piece length from the total size of the included files, keeping the number of
+    /// pieces manageable for large torrents without making small ones too coarse:
+    ///
+    /// | Total size   | Piece length |
+    /// |--------------|--------------|
+    /// | < 512 MiB    | 256 KiB      |
+    /// | < 2 GiB      | 512 KiB      |
+    /// | < 8 GiB      | 1 MiB        |
+    /// | < 32 GiB     | 2 MiB        |
+    /// | otherwise    | 4 MiB        |
+    Auto,
+}
+
+impl Default for PieceSelection {
+    /// A fixed piece length of 1 MiB, like [`create_torrent_file`](crate::create_torrent_file).
+    fn default() -> Self {
+        PieceSelection::Fixed(DEFAULT_PIECE_LENGTH)
+    }
+}
+
+impl PieceSelection {
+    /// The piece length in bytes for a torrent of `total_size` bytes.
+    pub fn piece_length(&self, total_size: i64) -> i64 {
+        match *self {
+            PieceSelection::Fixed(piece_length) => piece_length,
+            PieceSelection::Auto => AUTO_PIECE_LENGTHS
+                .iter()
+                .find(|&&(bound, _)| total_size < bound)
+                .map_or(AUTO_MAX_PIECE_LENGTH, |&(_, piece_length)| piece_length),
+        }
+    }
+}
+
+/// Options for [`create_torrent_file_with_options`].
+#[derive(Debug, Clone)]
+pub struct CreateTorrentOptions {
+    /// The tracker announce URL, if any.
+    pub tracker_url: Option<String>,
+    /// Glob patterns of files to leave out of the torrent, e.g. `.DS_Store` or `*.part`.
+    /// Patterns are matched against both the file name and the path relative to the folder,
+    /// and support the `*` and `?` wildcards.
+    pub exclude: Vec<String>,
+    /// The `source` tag stored in the `info` dictionary. Private trackers use it to give the
+    /// torrent an info hash unique to the tracker, which enables cross-seeding: the same folder
+    /// created with different sources yields different info hashes. `None` omits the key.
+    pub source: Option<String>,
+    /// How the piece length is chosen, 1 MiB by default.
+    pub piece_selection: PieceSelection,
+    /// Whether to include zero-byte files, e.g. marker files. Defaults to `true`.
+    pub include_empty_files: bool,
+    /// Whether to mark the torrent private, restricting peer discovery to its trackers. Private
+    /// trackers reject torrents without the flag.
+    pub private: bool,
+}
+
+impl Default for CreateTorrentOptions {
+    fn default() -> Self {
+        Self {
+            tracker_url: None,
+            exclude: Vec::new(),
+            source: None,
+            piece_selection: PieceSelection::default(),
+            include_empty_files: true,
+            private: false,
+        }
+    }
+}
+
+/// Advisory findings about the options a torrent was created with, as returned by
+/// [`create_torrent_file_checked`]. None of them prevent the torrent from being used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreateTorrentWarnings {
+    /// A tracker is set but the torrent is not private. Private trackers reject such torrents,
+    /// see [`CreateTorrentOptions::private`].
+    pub public_with_tracker: bool,
+    /// No tracker is set, so peers can only be found through DHT, PEX or local discovery.
+    pub no_tracker: bool,
+}
+
+impl CreateTorrentWarnings {
+    /// Checks the options for likely mistakes.
+    pub fn check(options: &CreateTorrentOptions) -> Self {
+        Self {
+            public_with_tracker: options.tracker_url.is_some() && !options.private,
+            no_tracker: options.tracker_url.is_none(),
+        }
+    }
+
+    /// Whether there is nothing to warn about.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Create a torrent file like [`create_torrent_file_with_options`], additionally returning
+/// [`CreateTorrentWarnings`] about the options. Warnings are advisory: the torrent is created
+/// either way.
+pub fn create_torrent_file_checked(
+    folder: &str,
+    output_file: &str,
+    options: &CreateTorrentOptions,
+) -> Result<CreateTorrentWarnings, BitTorrentError> {
+    create_torrent_file_with_options(folder, output_file, options)?;
+    Ok(CreateTorrentWarnings::check(options))
+}
+
+/// Create a torrent file from a folder, applying the given [`CreateTorrentOptions`].
+///
+/// The folder is walked and filtered before hashing, so excluded files are never read. Because
+/// the torrent is built from the filtered file list, the output may differ from
+/// [`create_torrent_file`](crate::create_torrent_file) for the same folder.
+pub fn create_torrent_file_with_options(
+    folder: &str,
+    output_file: &str,
+    options: &CreateTorrentOptions,
+) -> Result<(), BitTorrentError> {
+    let root = Path::new(folder);
+    let name = root
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            BitTorrentError::FileSystem(format!("cannot derive a torrent name from {}", folder))
+        })?
+        .to_owned();
+
+    let mut files = Vec::new();
+    collect_files(root, Path::new(""), &mut Vec::new(), &mut files)?;
+    files.retain(|(path, length)| {
+        (options.include_empty_files || *length > 0) && !is_excluded(path, &options.exclude)
+    });
+    files.sort();
+    if files.is_empty() {
+        return Err(BitTorrentError::InvalidTorrent(format!(
+            "no files to include in torrent for {}",
+            folder
+        )));
+    }
+
+    let total_size = files.iter().map(|(_, length)| length).sum();
+    let piece_length = options.piece_selection.piece_length(total_size);
+    if piece_length <= 0 || piece_length.count_ones() != 1 {
+        return Err(BitTorrentError::InvalidTorrent(format!(
+            "piece length must be a power of two, got {}",
+            piece_length
+        )));
+    }
+
+    let torrent = build_metainfo(root, name, files, piece_length, options)?;
+    torrent.write_into_file(output_file).map_err(|e| {
+        BitTorrentError::InvalidTorrent(format!("failed to write torrent file: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Recursively collects the regular files below `dir`, as paths relative to the torrent root
+/// together with their length.
+///
+/// Symbolic links are followed, except to a directory that is already being walked, as `ancestors`
+/// holds the canonical paths of `dir` and its parents. Such a link would otherwise be followed
+/// forever.
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    ancestors: &mut Vec<PathBuf>,
+    files: &mut Vec<(PathBuf, i64)>,
+) -> Result<(), BitTorrentError> {
+    let canonical = fs::canonicalize(root.join(dir)).map_err(|e| {
+        BitTorrentError::FileSystem(format!(
+            "failed to resolve {}: {}",
+            root.join(dir).display(),
+            e
+        ))
+    })?;
+    if ancestors.contains(&canonical) {
+        return Ok(());
+    }
+    ancestors.push(canonical);
+
+    let entries = fs::read_dir(root.join(dir)).map_err(|e| {
+        BitTorrentError::FileSystem(format!(
+            "failed to read {}: {}",
+            root.join(dir).display(),
+            e
+        ))
+    })?;
+    for entry in entries {
+        let entry = entry?;
+        let relative = dir.join(entry.file_name());
+        let metadata = fs::metadata(entry.path()).map_err(|e| {
+            BitTorrentError::FileSystem(format!("failed to stat {}: {}", entry.path().display(), e))
+        })?;
+        if metadata.is_dir() {
+            collect_files(root, &relative, ancestors, files)?;
+        } else if metadata.is_file() {
+            files.push((relative, metadata.len() as i64));
+        }
+    }
+    ancestors.pop();
+
+    Ok(())
+}
+
+/// Returns whether the relative `path` matches any of the exclude patterns.
+fn is_excluded(path: &Path, patterns: &[String]) -> bool {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let relative = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    patterns
+        .iter()
+        .any(|pattern| glob_match(pattern, &file_name) || glob_match(pattern, &relative))
+}
+
+/// Matches `text` against a glob `pattern` supporting `*` (any run of characters) and `?` (any
+/// single character).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen, and the text position it is currently matched up to.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Hashes the given files in order and builds a v1 multi-file torrent from them.
+fn build_metainfo(
+    root: &Path,
+    name: String,
+    files: Vec<(PathBuf, i64)>,
+    piece_length: i64,
+    options: &CreateTorrentOptions,
+) -> Result<Metainfo, BitTorrentError> {
+    let mut pieces = Vec::new();
+    let mut hasher = Sha1::new();
+    let mut piece_filled: i64 = 0;
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+
+    for (path, _) in &files {
+        let full_path = root.join(path);
+        let mut file = File::open(&full_path).map_err(|e| {
+            BitTorrentError::FileSystem(format!("failed to open {}: {}", full_path.display(), e))
+        })?;
+        loop {
+            let read = file.read(&mut buffer).map_err(|e| {
+                BitTorrentError::FileSystem(format!(
+                    "failed to read {}: {}",
+                    full_path.display(),
+                    e
+                ))
+            })?;
+            if read == 0 {
+                break;
+            }
+            let mut chunk = &buffer[..read];
+            while !chunk.is_empty() {
+                let take = chunk.len().min((piece_length - piece_filled) as usize);
+                hasher.update(&chunk[..take]);
+                piece_filled += take as i64;
+                chunk = &chunk[take..];
+                if piece_filled == piece_length {
+                    pieces.push(hasher.finalize_reset().to_vec());
+                    piece_filled = 0;
+                }
+            }
+        }
+    }
+    if piece_filled > 0 {
+        pieces.push(hasher.finalize().to_vec());
+    }
+
+    let length = files.iter().map(|(_, length)| length).sum();
+    let files = files
+        .into_iter()
+        .map(|(path, length)| MetainfoFile {
+            length,
+            path,
+            extra_fields: None,
+        })
+        .collect();
+    let mut extra_info_fields = HashMap::new();
+    if let Some(source) = &options.source {
+        extra_info_fields.insert("source".to_string(), BencodeElem::String(source.clone()));
+    }
+    if options.private {
+        extra_info_fields.insert("private".to_string(), BencodeElem::Integer(1));
+    }
+    let extra_info_fields = (!extra_info_fields.is_empty()).then_some(extra_info_fields);
+
+    Ok(Metainfo {
+        announce: options.tracker_url.clone(),
+        announce_list: None,
+        length,
+        files: Some(files),
+        name,
+        piece_length,
+        pieces,
+        extra_fields: None,
+        extra_info_fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lava_torrent::torrent::v1::Torrent as Metainfo;
+
+    use super::*;
+
+    #[test]
+    fn glob_matching() {
+        assert!(glob_match(".DS_Store", ".DS_Store"));
+        assert!(glob_match("*.part", "movie.mkv.part"));
+        assert!(glob_match("Thumbs.d?", "Thumbs.db"));
+        assert!(glob_match("cache/*", "cache/a/b.bin"));
+        assert!(!glob_match("*.part", "movie.mkv"));
+        assert!(!glob_match("Thumbs.d?", "Thumbs.d"));
+    }
+
+    #[test]
+    fn auto_piece_length_thresholds() {
+        let auto = |total_size| PieceSelection::Auto.piece_length(total_size);
+        assert_eq!(auto(0), 256 * KIB);
+        assert_eq!(auto(512 * MIB - 1), 256 * KIB);
+        assert_eq!(auto(512 * MIB), 512 * KIB);
+        assert_eq!(auto(2 * GIB - 1), 512 * KIB);
+        assert_eq!(auto(2 * GIB), MIB);
+        assert_eq!(auto(8 * GIB), 2 * MIB);
+        assert_eq!(auto(32 * GIB - 1), 2 * MIB);
+        assert_eq!(auto(32 * GIB), 4 * MIB);
+        assert_eq!(auto(i64::MAX), 4 * MIB);
+    }
+
+    #[test]
+    fn default_piece_selection_is_fixed() {
+        assert_eq!(
+            PieceSelection::default().piece_length(64 * GIB),
+            DEFAULT_PIECE_LENGTH
+        );
+    }
+
+    #[test]
+    fn create_torrent_with_auto_piece_length() -> Result<(), BitTorrentError> {
+        let dir = "target/test_data/create_torrent_auto_piece_length";
+        let folder = format!("{dir}/folder");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+
+        let output = format!("{dir}/test.torrent");
+        let options = CreateTorrentOptions {
+            piece_selection: PieceSelection::Auto,
+            ..Default::default()
+        };
+        create_torrent_file_with_options(&folder, &output, &options)?;
+        assert_eq!(
+            Metainfo::read_from_file(&output).unwrap().piece_length,
+            256 * KIB
+        );
+
+        let options = CreateTorrentOptions {
+            piece_selection: PieceSelection::Fixed(1000),
+            ..Default::default()
+        };
+        assert!(matches!(
+            create_torrent_file_with_options(&folder, &output, &options),
+            Err(BitTorrentError::InvalidTorrent(_))
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn create_torrent_excludes_patterns() -> Result<(), BitTorrentError> {
+        let folder = "target/test_data/create_torrent_exclude/folder";
+        std::fs::create_dir_all(format!("{folder}/nested")).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+        std::fs::write(format!("{folder}/.DS_Store"), "junk").unwrap();
+        std::fs::write(format!("{folder}/nested/Thumbs.db"), "junk").unwrap();
+        std::fs::write(format!("{folder}/nested/data.bin.part"), "partial").unwrap();
+        std::fs::write(format!("{folder}/nested/data.txt"), "nested file").unwrap();
+
+        let output = "target/test_data/create_torrent_exclude/test.torrent";
+        let options = CreateTorrentOptions {
+            exclude: vec![
+                ".DS_Store".to_string(),
+                "Thumbs.db".to_string(),
+                "*.part".to_string(),
+            ],
+            ..Default::default()
+        };
+        create_torrent_file_with_options(folder, output, &options)?;
+
+        let torrent = Metainfo::read_from_file(output).unwrap();
+        let paths: Vec<PathBuf> = torrent
+            .files
+            .unwrap()
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("file.txt"), PathBuf::from("nested/data.txt")]
+        );
+        std::fs::remove_dir_all("target/test_data/create_torrent_exclude").unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn create_torrent_skips_symlink_cycles() -> Result<(), BitTorrentError> {
+        let folder = "target/test_data/create_torrent_symlink_cycle/folder";
+        std::fs::create_dir_all(format!("{folder}/nested")).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+        std::fs::write(format!("{folder}/nested/data.txt"), "nested file").unwrap();
+        std::os::unix::fs::symlink("..", format!("{folder}/nested/loop")).unwrap();
+
+        let output = "target/test_data/create_torrent_symlink_cycle/test.torrent";
+        create_torrent_file_with_options(folder, output, &CreateTorrentOptions::default())?;
+
+        let torrent = Metainfo::read_from_file(output).unwrap();
+        let paths: Vec<PathBuf> = torrent
+            .files
+            .unwrap()
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("file.txt"), PathBuf::from("nested/data.txt")]
+        );
+        std::fs::remove_dir_all("target/test_data/create_torrent_symlink_cycle").unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn create_torrent_empty_files() -> Result<(), BitTorrentError> {
+        let dir = "target/test_data/create_torrent_empty_files";
+        let folder = format!("{dir}/folder");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+        std::fs::write(format!("{folder}/.done"), "").unwrap();
+
+        let paths = |include_empty_files| -> Result<Vec<PathBuf>, BitTorrentError> {
+            let output = format!("{dir}/test.torrent");
+            let options = CreateTorrentOptions {
+                include_empty_files,
+                ..Default::default()
+            };
+            create_torrent_file_with_options(&folder, &output, &options)?;
+            let torrent = Metainfo::read_from_file(&output).unwrap();
+            Ok(torrent
+                .files
+                .unwrap()
+                .into_iter()
+                .map(|file| file.path)
+                .collect())
+        };
+
+        assert_eq!(
+            paths(true)?,
+            vec![PathBuf::from(".done"), PathBuf::from("file.txt")]
+        );
+        assert_eq!(paths(false)?, vec![PathBuf::from("file.txt")]);
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn warnings_for_public_torrent_with_tracker() -> Result<(), BitTorrentError> {
+        let dir = "target/test_data/create_torrent_checked_public";
+        let folder = format!("{dir}/folder");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+
+        let output = format!("{dir}/test.torrent");
+        let options = CreateTorrentOptions {
+            tracker_url: Some("udp://tracker.example:1337/announce".to_string()),
+            ..Default::default()
+        };
+        let warnings = create_torrent_file_checked(&folder, &output, &options)?;
+        assert_eq!(
+            warnings,
+            CreateTorrentWarnings {
+                public_with_tracker: true,
+                no_tracker: false,
+            }
+        );
+        assert!(Path::new(&output).exists());
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn warnings_without_tracker() {
+        let warnings = CreateTorrentWarnings::check(&CreateTorrentOptions::default());
+        assert_eq!(
+            warnings,
+            CreateTorrentWarnings {
+                public_with_tracker: false,
+                no_tracker: true,
+            }
+        );
+    }
+
+    #[test]
+    fn no_warnings_for_private_torrent_with_tracker() -> Result<(), BitTorrentError> {
+        let dir = "target/test_data/create_torrent_checked_private";
+        let folder = format!("{dir}/folder");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+
+        let output = format!("{dir}/test.torrent");
+        let options = CreateTorrentOptions {
+            tracker_url: Some("udp://tracker.example:1337/announce".to_string()),
+            private: true,
+            ..Default::default()
+        };
+        let warnings = create_torrent_file_checked(&folder, &output, &options)?;
+        assert!(warnings.is_empty());
+        assert!(crate::validate_torrent_file(&output)?.private);
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn create_torrent_with_source() -> Result<(), BitTorrentError> {
+        let folder = "target/test_data/create_torrent_source/folder";
+        std::fs::create_dir_all(folder).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+
+        let output = "target/test_data/create_torrent_source/test.torrent";
+        let options = CreateTorrentOptions {
+            source: Some("MOSAIC".to_string()),
+            ..Default::default()
+        };
+        create_torrent_file_with_options(folder, output, &options)?;
+
+        let torrent = Metainfo::read_from_file(output).unwrap();
+        let source = torrent
+            .extra_info_fields
+            .as_ref()
+            .and_then(|fields| fields.get("source"));
+        assert!(matches!(source, Some(BencodeElem::String(s)) if s == "MOSAIC"));
+        std::fs::remove_dir_all("target/test_data/create_torrent_source").unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn source_changes_info_hash() -> Result<(), BitTorrentError> {
+        let dir = "target/test_data/create_torrent_source_hash";
+        let folder = format!("{dir}/folder");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+
+        let info_hash = |name: &str, source: Option<&str>| -> Result<String, BitTorrentError> {
+            let output = format!("{dir}/{name}.torrent");
+            let options = CreateTorrentOptions {
+                source: source.map(str::to_owned),
+                ..Default::default()
+            };
+            create_torrent_file_with_options(&folder, &output, &options)?;
+            Ok(Metainfo::read_from_file(&output).unwrap().info_hash())
+        };
+
+        let tracker_a = info_hash("a", Some("TRACKER-A"))?;
+        let tracker_b = info_hash("b", Some("TRACKER-B"))?;
+        let tracker_a_again = info_hash("a_again", Some("TRACKER-A"))?;
+        assert_ne!(tracker_a, tracker_b);
+        assert_eq!(tracker_a, tracker_a_again);
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn create_torrent_without_source() -> Result<(), BitTorrentError> {
+        let folder = "target/test_data/create_torrent_no_source/folder";
+        std::fs::create_dir_all(folder).unwrap();
+        std::fs::write(format!("{folder}/file.txt"), "This is a test file.").unwrap();
+
+        let output = "target/test_data/create_torrent_no_source/test.torrent";
+        create_torrent_file_with_options(folder, output, &CreateTorrentOptions::default())?;
+
+        let torrent = Metainfo::read_from_file(output).unwrap();
+        assert!(
+            torrent
+                .extra_info_fields
+                .is_none_or(|fields| !fields.contains_key("source"))
+        );
+        std::fs::remove_dir_all("target/test_data/create_torrent_no_source").unwrap();
+        Ok(())
+    }
+}
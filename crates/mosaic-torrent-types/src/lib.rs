@@ -2,9 +2,28 @@
 //!
 //! This crate defines common types and traits for BitTorrent clients used in the Mosaic project.
 
-use lava_torrent::torrent::v1::TorrentBuilder;
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use lava_torrent::torrent::v1::{Torrent as Metainfo, TorrentBuilder};
 use thiserror::Error;
 
+mod create;
+mod metadata;
+mod validate;
+
+pub use create::{
+    CreateTorrentOptions, CreateTorrentWarnings, PieceSelection, create_torrent_file_checked,
+    create_torrent_file_with_options,
+};
+pub use metadata::{TorrentFileMeta, torrent_file_metadata};
+pub use validate::{TorrentSummary, validate_torrent_file};
+
 /// Error type for BitTorrent operations.
 #[derive(Error, Debug)]
 pub enum BitTorrentError {
@@ -28,11 +47,48 @@ pub enum BitTorrentError {
     #[error("file system: {0}")]
     FileSystem(String),
 
+    /// The daemon speaks an RPC version older than the minimum supported one.
+    #[error("unsupported RPC version {found}, minimum supported is {minimum}")]
+    UnsupportedVersion {
+        /// The RPC version reported by the daemon.
+        found: String,
+        /// The minimum supported RPC version.
+        minimum: String,
+    },
+
+    /// There is not enough free disk space for the torrent.
+    #[error("insufficient free space: {required} bytes required, {available} bytes available")]
+    InsufficientSpace {
+        /// The number of bytes the torrent needs.
+        required: i64,
+        /// The number of bytes available.
+        available: i64,
+    },
+
+    /// The operation did not finish in time.
+    #[error("timed out: {0}")]
+    Timeout(String),
+
+    /// The operation was cancelled before it finished.
+    #[error("cancelled")]
+    Cancelled,
+
+    /// The daemon does not implement the requested operation.
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+
     /// Other unexpected errors
     #[error("unexpected: {0}")]
     Other(String),
 }
 
+/// Maps to [`BitTorrentError::FileSystem`], keeping the error kind in the message.
+impl From<std::io::Error> for BitTorrentError {
+    fn from(err: std::io::Error) -> Self {
+        BitTorrentError::FileSystem(format!("{:?}: {}", err.kind(), err))
+    }
+}
+
 /// Create a torrent file from a folder.
 /// This is not BitTorrent client specific, so it is not part of the BitTorrent trait.
 pub fn create_torrent_file(
@@ -40,10 +96,33 @@ pub fn create_torrent_file(
     output_file: &str,
     tracker_url: Option<String>,
 ) -> Result<(), BitTorrentError> {
-    let torrent = TorrentBuilder::new(folder, 1048576)
-        .set_announce(tracker_url)
-        .build()
-        .unwrap();
+    let torrent = build_torrent(folder, tracker_url)?;
+    torrent.write_into_file(output_file).map_err(|e| {
+        BitTorrentError::InvalidTorrent(format!("failed to write torrent file: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Create a single-file torrent from a regular file.
+///
+/// Unlike a torrent created from a folder holding only that file, the `info` dictionary has a
+/// `length` instead of a `files` list, so clients download the file without an enclosing
+/// directory.
+pub fn create_torrent_from_file(
+    file: &str,
+    output_file: &str,
+    tracker_url: Option<&str>,
+) -> Result<(), BitTorrentError> {
+    let metadata = std::fs::metadata(file)?;
+    if !metadata.is_file() {
+        return Err(BitTorrentError::FileSystem(format!(
+            "not a regular file: {}",
+            file
+        )));
+    }
+
+    let torrent = build_torrent(file, tracker_url.map(str::to_owned))?;
     torrent.write_into_file(output_file).map_err(|e| {
         BitTorrentError::InvalidTorrent(format!("failed to write torrent file: {}", e))
     })?;
@@ -51,6 +130,81 @@ pub fn create_torrent_file(
     Ok(())
 }
 
+/// Create a torrent file from a folder like [`create_torrent_file`], without blocking the async
+/// runtime. The folder is read and hashed on a blocking thread and the output is written with
+/// async I/O, so this is safe to call from an async context.
+pub async fn create_torrent_file_async(
+    folder: &str,
+    output_file: &str,
+    tracker_url: Option<String>,
+) -> Result<(), BitTorrentError> {
+    let folder = folder.to_owned();
+    let bytes = tokio::task::spawn_blocking(move || {
+        build_torrent(&folder, tracker_url)?.encode().map_err(|e| {
+            BitTorrentError::InvalidTorrent(format!("failed to encode torrent: {}", e))
+        })
+    })
+    .await
+    .map_err(|e| BitTorrentError::Other(format!("torrent creation task failed: {}", e)))??;
+    tokio::fs::write(output_file, bytes)
+        .await
+        .map_err(|e| BitTorrentError::FileSystem(format!("failed to write torrent file: {}", e)))?;
+
+    Ok(())
+}
+
+/// Hashes the file or folder and builds its torrent.
+fn build_torrent(folder: &str, tracker_url: Option<String>) -> Result<Metainfo, BitTorrentError> {
+    TorrentBuilder::new(folder, create::DEFAULT_PIECE_LENGTH)
+        .set_announce(tracker_url)
+        .build()
+        .map_err(|e| BitTorrentError::InvalidTorrent(format!("failed to build torrent: {}", e)))
+}
+
+/// Create a torrent file from a folder, deriving the output file name from the folder name.
+/// The torrent is written to `<output_dir>/<folder_basename>.torrent` and the path is returned.
+/// An existing file at that path is never overwritten, a collision is reported as an error instead.
+pub fn create_torrent_file_auto(
+    folder: &str,
+    output_dir: &str,
+    tracker_url: Option<&str>,
+) -> Result<PathBuf, BitTorrentError> {
+    let folder_name = Path::new(folder).file_name().ok_or_else(|| {
+        BitTorrentError::FileSystem(format!("cannot derive a torrent name from {}", folder))
+    })?;
+    let mut file_name = folder_name.to_os_string();
+    file_name.push(".torrent");
+    let output_file = Path::new(output_dir).join(file_name);
+
+    let bytes = build_torrent(folder, tracker_url.map(str::to_owned))?
+        .encode()
+        .map_err(|e| BitTorrentError::InvalidTorrent(format!("failed to encode torrent: {}", e)))?;
+    // Creating the file fails if it exists, so a file created concurrently is not overwritten.
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&output_file)
+        .map_err(|e| match e.kind() {
+            ErrorKind::AlreadyExists => BitTorrentError::FileSystem(format!(
+                "torrent file already exists: {}",
+                output_file.display()
+            )),
+            _ => e.into(),
+        })?;
+    file.write_all(&bytes)?;
+
+    Ok(output_file)
+}
+
+/// Read the total size in bytes of the content described by a local torrent file.
+pub fn torrent_file_size(torrent_file: &str) -> Result<i64, BitTorrentError> {
+    let torrent = Metainfo::read_from_file(torrent_file).map_err(|e| {
+        BitTorrentError::InvalidTorrent(format!("failed to read torrent file: {}", e))
+    })?;
+
+    Ok(torrent.length)
+}
+
 /// BitTorrent trait defines the common interface for BitTorrent clients.
 #[allow(async_fn_in_trait)]
 pub trait BitTorrent {
@@ -69,8 +223,48 @@ pub trait BitTorrent {
         ids: Vec<String>,
         delete_local_data: bool,
     ) -> Result<(), BitTorrentError>;
+    /// List all torrents, grouped by their [`TorrentStatus`]. Every status has an entry, even
+    /// if no torrent is currently in it.
+    async fn list_grouped(&self) -> Result<BTreeMap<TorrentStatus, Vec<Torrent>>, BitTorrentError> {
+        let mut grouped: BTreeMap<TorrentStatus, Vec<Torrent>> = TorrentStatus::ALL
+            .into_iter()
+            .map(|status| (status, Vec::new()))
+            .collect();
+        for torrent in self.list().await? {
+            grouped.entry(torrent.status()).or_default().push(torrent);
+        }
+        Ok(grouped)
+    }
     /// Get session statistics.
     async fn stats(&self) -> Result<SessionStats, BitTorrentError>;
+    /// Get all torrents together with the session statistics.
+    ///
+    /// Both are fetched back-to-back, which is as close to a consistent view as the RPC allows,
+    /// but it is not transactionally atomic: the state may change between the two requests.
+    async fn snapshot(&self) -> Result<Snapshot, BitTorrentError> {
+        let captured_at = SystemTime::now();
+        let torrents = self.list().await?;
+        let stats = self.stats().await?;
+        Ok(Snapshot {
+            torrents,
+            stats,
+            captured_at,
+        })
+    }
+    /// Check that the daemon is reachable and accepts our credentials, without side effects.
+    /// Fails with [`BitTorrentError::Unauthorized`] or [`BitTorrentError::Network`] respectively.
+    async fn ping(&self) -> Result<(), BitTorrentError>;
+    /// Set whether torrents (by hash) are subject to the session-wide speed limits. Torrents
+    /// that do not honor them are only limited by their own limits, if any.
+    async fn set_honors_session_limits(
+        &self,
+        ids: Vec<String>,
+        honors: bool,
+    ) -> Result<(), BitTorrentError>;
+    /// Cap the number of peers the given torrents (by hash) connect to. The current limit is
+    /// reported as [`Peers::peer_limit`]. Fails with [`BitTorrentError::Other`] if `limit` is
+    /// negative.
+    async fn set_peer_limit(&self, ids: Vec<String>, limit: i32) -> Result<(), BitTorrentError>;
 }
 
 // The below are mostly copied from Transmission RPC types, as this will be the initial implementation.
@@ -95,6 +289,17 @@ pub struct SessionStats {
     pub upload_speed: i32,
 }
 
+/// All torrents and the session statistics, captured together by [`BitTorrent::snapshot`].
+#[derive(Debug)]
+pub struct Snapshot {
+    /// All torrents.
+    pub torrents: Vec<Torrent>,
+    /// The session statistics.
+    pub stats: SessionStats,
+    /// When the snapshot was taken, i.e. right before the first request was sent.
+    pub captured_at: SystemTime,
+}
+
 /// Detailed statistics.
 #[derive(Debug)]
 #[allow(missing_docs)]
@@ -111,7 +316,7 @@ pub struct StatsDetails {
 }
 
 /// Torrent information.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(missing_docs)]
 pub struct Torrent {
     pub id: i32,
@@ -128,12 +333,18 @@ pub struct Torrent {
 
     pub date_created: i32,
 
+    /// How many of the bytes still needed are available from the connected peers.
+    pub desired_available: i64,
+
     pub download_dir: String,
 
     pub download_limit: i32,
 
     pub download_limited: bool,
 
+    /// When the torrent's settings were last changed on the daemon, as a Unix timestamp.
+    pub edit_date: i32,
+
     pub eta: i64,
 
     pub eta_idle: i64,
@@ -144,18 +355,45 @@ pub struct Torrent {
 
     pub have_valid: i64,
 
+    /// Whether the session-wide speed limits apply to the torrent.
+    pub honors_session_limits: bool,
+
     pub is_finished: bool,
 
     pub is_private: bool,
 
     pub is_stalled: bool,
 
+    /// How many bytes of the wanted data are still to be downloaded.
+    pub left_until_done: i64,
+
+    /// How much of the torrent's metadata is known, between `0.0` and `1.0`. Below `1.0` for
+    /// magnet links until the metadata has been fetched from peers.
+    pub metadata_percent_complete: f32,
+
     pub name: String,
 
     pub percent_done: f32,
 
     pub queue_position: i32,
 
+    /// The current download speed, in bytes per second.
+    pub rate_download: i64,
+
+    /// The current upload speed, in bytes per second.
+    pub rate_upload: i64,
+
+    /// Minutes of inactivity after which the torrent stops seeding, see `seed_idle_mode`.
+    pub seed_idle_limit: i32,
+
+    /// Which idle limit applies: 0 follows the session setting, 1 uses `seed_idle_limit` and
+    /// 2 seeds regardless of inactivity.
+    pub seed_idle_mode: i32,
+
+    /// How many bytes of the torrent's wanted files there are, i.e. `total_size` without the
+    /// files that are not wanted.
+    pub size_when_done: i64,
+
     pub start_date: i32,
 
     pub status: i32,
@@ -165,12 +403,266 @@ pub struct Torrent {
     pub total_size: i64,
 }
 
+impl Torrent {
+    /// The key identifying this torrent by its info hash, see [`TorrentKey`].
+    pub fn key(&self) -> TorrentKey {
+        TorrentKey::new(&self.hash_string)
+    }
+
+    /// The typed activity status of the torrent.
+    pub fn status(&self) -> TorrentStatus {
+        TorrentStatus::from(self.status)
+    }
+
+    /// Whether all wanted data has been downloaded.
+    ///
+    /// Unlike `is_finished`, which only becomes true once seeding has stopped, this is true as
+    /// soon as the download completes.
+    pub fn is_complete(&self) -> bool {
+        self.percent_done >= 1.0
+    }
+
+    /// How many bytes of the wanted data are still to be downloaded, see `left_until_done`.
+    pub fn bytes_remaining(&self) -> i64 {
+        self.left_until_done
+    }
+
+    /// The fraction of the data still needed that the connected peers can provide, between
+    /// `0.0` and `1.0`. Below `1.0`, the download cannot finish with the current peers. A
+    /// torrent with nothing left to download is fully available.
+    pub fn availability_fraction(&self) -> f64 {
+        if self.left_until_done <= 0 {
+            return 1.0;
+        }
+        (self.desired_available as f64 / self.left_until_done as f64).clamp(0.0, 1.0)
+    }
+
+    /// The directory the torrent's data currently lives in: `incomplete_dir` while it is
+    /// downloading, and `download_dir` once complete, as that is when the daemon moves the data.
+    /// Pass `None` if the daemon keeps incomplete downloads in the download directory.
+    pub fn active_dir<'a>(&'a self, incomplete_dir: Option<&'a str>) -> &'a str {
+        match incomplete_dir {
+            Some(incomplete_dir) if !self.is_complete() => incomplete_dir,
+            _ => &self.download_dir,
+        }
+    }
+
+    /// When the torrent was added, or `None` if unknown.
+    pub fn added_at(&self) -> Option<SystemTime> {
+        unix_time(self.added_date)
+    }
+
+    /// When the torrent was last started, or `None` if it never was.
+    pub fn started_at(&self) -> Option<SystemTime> {
+        unix_time(self.start_date)
+    }
+
+    /// When data was last sent or received for the torrent, or `None` if it never was.
+    pub fn last_activity_at(&self) -> Option<SystemTime> {
+        unix_time(self.activity_date)
+    }
+
+    /// When the torrent's settings were last changed on the daemon, or `None` if they never were.
+    pub fn edited_at(&self) -> Option<SystemTime> {
+        unix_time(self.edit_date)
+    }
+
+    /// When anything about the torrent last changed on the daemon: the latest of when it was
+    /// added, last had activity and last had its settings changed.
+    pub fn changed_at(&self) -> Option<SystemTime> {
+        [self.added_at(), self.last_activity_at(), self.edited_at()]
+            .into_iter()
+            .flatten()
+            .max()
+    }
+
+    /// When the torrent file was created, or `None` if it does not say.
+    pub fn created_at(&self) -> Option<SystemTime> {
+        unix_time(self.date_created)
+    }
+
+    /// A concise one-line summary for CLI output: id, name, progress, status and the
+    /// download/upload speeds, e.g. `1 ubuntu.iso 50.0% downloading 1.5 MiB/s / 0 B/s`.
+    pub fn summary_line(&self) -> String {
+        let [id, name, progress, status, download, upload] = self.summary_columns();
+        format!("{id} {name} {progress} {status} {download} / {upload}")
+    }
+
+    /// The columns of [`Torrent::summary_line`] for table renderers: id, name, progress,
+    /// status, download speed and upload speed.
+    pub fn summary_table_row(&self) -> Vec<String> {
+        self.summary_columns().into()
+    }
+
+    fn summary_columns(&self) -> [String; 6] {
+        [
+            self.id.to_string(),
+            self.name.clone(),
+            format!("{:.1}%", self.percent_done * 100.0),
+            self.status().as_str().to_string(),
+            format_rate(self.rate_download),
+            format_rate(self.rate_upload),
+        ]
+    }
+}
+
+/// Formats a speed in bytes per second with binary units, e.g. `1.5 MiB/s`.
+fn format_rate(bytes_per_sec: i64) -> String {
+    const UNITS: [&str; 4] = ["KiB/s", "MiB/s", "GiB/s", "TiB/s"];
+    if bytes_per_sec < 1024 {
+        return format!("{bytes_per_sec} B/s");
+    }
+    let mut value = bytes_per_sec as f64;
+    let mut unit = "B/s";
+    for next in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+/// Converts a Unix timestamp in seconds to a [`SystemTime`]. Transmission reports unset
+/// timestamps as `0`, which map to `None`.
+fn unix_time(secs: i32) -> Option<SystemTime> {
+    u64::try_from(secs)
+        .ok()
+        .filter(|&secs| secs > 0)
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Identifies a torrent by its info hash, e.g. to deduplicate torrents in a `HashSet` or to key
+/// a `HashMap`. The hash is normalized to lowercase, so keys compare equal regardless of casing.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TorrentKey(String);
+
+impl TorrentKey {
+    /// Creates a key from a hex-encoded info hash.
+    pub fn new(hash: &str) -> Self {
+        Self(hash.to_ascii_lowercase())
+    }
+
+    /// The normalized info hash.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&Torrent> for TorrentKey {
+    fn from(torrent: &Torrent) -> Self {
+        torrent.key()
+    }
+}
+
+/// Activity status of a torrent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TorrentStatus {
+    /// The torrent is stopped.
+    Stopped,
+    /// The torrent is queued for verification of its local data.
+    QueuedToVerify,
+    /// The local data of the torrent is being verified.
+    Verifying,
+    /// The torrent is queued for downloading.
+    QueuedToDownload,
+    /// The torrent is downloading.
+    Downloading,
+    /// The torrent is queued for seeding.
+    QueuedToSeed,
+    /// The torrent is seeding.
+    Seeding,
+    /// The client reported a status that is not known to us.
+    Unknown,
+}
+
+impl TorrentStatus {
+    /// All statuses, in order.
+    pub const ALL: [TorrentStatus; 8] = [
+        TorrentStatus::Stopped,
+        TorrentStatus::QueuedToVerify,
+        TorrentStatus::Verifying,
+        TorrentStatus::QueuedToDownload,
+        TorrentStatus::Downloading,
+        TorrentStatus::QueuedToSeed,
+        TorrentStatus::Seeding,
+        TorrentStatus::Unknown,
+    ];
+
+    /// A short lowercase name for the status, e.g. for CLI output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TorrentStatus::Stopped => "stopped",
+            TorrentStatus::QueuedToVerify => "queued to verify",
+            TorrentStatus::Verifying => "verifying",
+            TorrentStatus::QueuedToDownload => "queued to download",
+            TorrentStatus::Downloading => "downloading",
+            TorrentStatus::QueuedToSeed => "queued to seed",
+            TorrentStatus::Seeding => "seeding",
+            TorrentStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl From<i32> for TorrentStatus {
+    /// Converts the raw status codes as used by Transmission.
+    fn from(status: i32) -> Self {
+        match status {
+            0 => TorrentStatus::Stopped,
+            1 => TorrentStatus::QueuedToVerify,
+            2 => TorrentStatus::Verifying,
+            3 => TorrentStatus::QueuedToDownload,
+            4 => TorrentStatus::Downloading,
+            5 => TorrentStatus::QueuedToSeed,
+            6 => TorrentStatus::Seeding,
+            _ => TorrentStatus::Unknown,
+        }
+    }
+}
+
+/// Whether a torrent's local data is verified after it is added, see [`AddOptions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerifyMode {
+    /// Leave it to the client. Transmission checks existing data when it first starts the
+    /// torrent, and skips the check if there is none.
+    #[default]
+    Auto,
+    /// Request a full verification right after the add, e.g. for data of doubtful origin.
+    Always,
+    /// Don't request a verification, e.g. for seeds freshly created from trusted data. The
+    /// client may still check existing data on its own, which not every daemon lets us skip.
+    Never,
+}
+
+/// Options for adding a torrent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddOptions {
+    /// Whether to verify the torrent's local data after adding it.
+    pub verify_on_add: VerifyMode,
+}
+
+/// A torrent returned by an add, along with details about how the add went.
+#[derive(Debug)]
+pub struct AddedTorrent {
+    /// The added torrent.
+    pub torrent: Torrent,
+    /// Whether the torrent metadata is available. This is `false` for magnets whose metadata is
+    /// still being resolved, in which case most fields of `torrent` are still empty.
+    pub metadata_complete: bool,
+    /// Whether the torrent was already present in the client before the add.
+    pub duplicate: bool,
+}
+
 /// Torrent peers information.
 #[derive(Debug)]
 #[allow(missing_docs)]
 pub struct Peers {
     pub id: i32,
 
+    /// The info hash of the torrent these peers belong to.
+    pub hash_string: String,
+
     pub peer_limit: i32,
 
     pub peers_connected: i32,
@@ -184,8 +676,102 @@ pub struct Peers {
     pub webseeds_sending_to_us: i32,
 }
 
+/// Peer counts summed across all torrents of a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerTotals {
+    /// The number of connected peers.
+    pub connected: i32,
+    /// The number of peers we are downloading from.
+    pub sending_to_us: i32,
+    /// The number of peers we are uploading to.
+    pub getting_from_us: i32,
+}
+
+/// Version and location details of the BitTorrent daemon, for diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaemonInfo {
+    /// The daemon's version string, e.g. `4.0.5 (a6fe2a64aa)`.
+    pub version: String,
+    /// The version of the RPC protocol spoken by the daemon.
+    pub rpc_version: i64,
+    /// The directory the daemon keeps its configuration and state in.
+    pub config_dir: String,
+}
+
+/// The per-torrent speed limits in effect, in KB/s. `None` means the torrent has no limit of
+/// its own in that direction, although the session-wide limits may still apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpeedLimits {
+    /// The download limit.
+    pub download: Option<i32>,
+    /// The upload limit.
+    pub upload: Option<i32>,
+}
+
 #[cfg(test)]
 mod tests {
+    fn make_torrent(id: i32, hash: &str) -> super::Torrent {
+        super::Torrent {
+            id,
+            activity_date: 0,
+            added_date: 0,
+            bandwidth_priority: 0,
+            comment: String::new(),
+            creator: String::new(),
+            date_created: 0,
+            desired_available: 0,
+            download_dir: "/downloads".to_string(),
+            download_limit: 0,
+            download_limited: false,
+            edit_date: 0,
+            eta: 0,
+            eta_idle: 0,
+            hash_string: hash.to_string(),
+            have_unchecked: 0,
+            have_valid: 0,
+            honors_session_limits: true,
+            is_finished: false,
+            is_private: false,
+            is_stalled: false,
+            left_until_done: 0,
+            metadata_percent_complete: 1.0,
+            name: format!("torrent{id}"),
+            percent_done: 0.5,
+            queue_position: 0,
+            rate_download: 0,
+            rate_upload: 0,
+            seed_idle_limit: 0,
+            seed_idle_mode: 0,
+            size_when_done: 1000,
+            start_date: 0,
+            status: 4,
+            torrent_file: "/path/to/torrent".to_string(),
+            total_size: 1000,
+        }
+    }
+
+    #[test]
+    fn torrent_key_dedup() {
+        let torrents = vec![
+            make_torrent(1, "abc123"),
+            make_torrent(2, "def456"),
+            make_torrent(3, "ABC123"),
+        ];
+
+        let keys: std::collections::HashSet<super::TorrentKey> =
+            torrents.iter().map(super::Torrent::key).collect();
+        assert_eq!(keys.len(), 2);
+        assert!(keys.contains(&super::TorrentKey::new("abc123")));
+        assert!(keys.contains(&super::TorrentKey::new("def456")));
+    }
+
+    #[test]
+    fn torrent_key_from_torrent() {
+        let torrent = make_torrent(1, "ABC123");
+        let key = super::TorrentKey::from(&torrent);
+        assert_eq!(key.as_str(), "abc123");
+    }
+
     #[test]
     fn create_torrent() -> Result<(), super::BitTorrentError> {
         std::fs::create_dir_all("target/test_data/create_torrent").unwrap();
@@ -203,4 +789,221 @@ mod tests {
         std::fs::remove_dir_all("target/test_data/create_torrent").unwrap();
         Ok(())
     }
+
+    #[test]
+    fn torrent_status_from_raw() {
+        use super::TorrentStatus;
+
+        assert_eq!(TorrentStatus::from(0), TorrentStatus::Stopped);
+        assert_eq!(TorrentStatus::from(2), TorrentStatus::Verifying);
+        assert_eq!(TorrentStatus::from(4), TorrentStatus::Downloading);
+        assert_eq!(TorrentStatus::from(6), TorrentStatus::Seeding);
+        assert_eq!(TorrentStatus::from(7), TorrentStatus::Unknown);
+        assert_eq!(TorrentStatus::from(-1), TorrentStatus::Unknown);
+    }
+
+    #[test]
+    fn create_torrent_from_single_file() -> Result<(), super::BitTorrentError> {
+        let dir = "target/test_data/create_torrent_from_file";
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(format!("{dir}/file.txt"), "This is a test file.").unwrap();
+        let output = format!("{dir}/file.torrent");
+
+        super::create_torrent_from_file(&format!("{dir}/file.txt"), &output, None)?;
+
+        let torrent = super::Metainfo::read_from_file(&output).unwrap();
+        assert_eq!(torrent.name, "file.txt");
+        assert!(torrent.files.is_none());
+        assert_eq!(torrent.length, 20);
+
+        let result = super::create_torrent_from_file(dir, &format!("{dir}/dir.torrent"), None);
+        assert!(matches!(result, Err(super::BitTorrentError::FileSystem(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn torrent_file_size() -> Result<(), super::BitTorrentError> {
+        std::fs::create_dir_all("target/test_data/torrent_file_size").unwrap();
+        std::fs::write("target/test_data/torrent_file_size/a.txt", "12345").unwrap();
+        std::fs::write("target/test_data/torrent_file_size/b.txt", "1234567890").unwrap();
+        super::create_torrent_file(
+            "target/test_data/torrent_file_size",
+            "target/test_data/torrent_file_size.torrent",
+            None,
+        )?;
+
+        let size = super::torrent_file_size("target/test_data/torrent_file_size.torrent")?;
+        assert_eq!(size, 15);
+        assert!(matches!(
+            super::torrent_file_size("target/test_data/torrent_file_size/a.txt"),
+            Err(super::BitTorrentError::InvalidTorrent(_))
+        ));
+        std::fs::remove_dir_all("target/test_data/torrent_file_size").unwrap();
+        std::fs::remove_file("target/test_data/torrent_file_size.torrent").unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn create_torrent_auto_derives_name() -> Result<(), super::BitTorrentError> {
+        std::fs::create_dir_all("target/test_data/create_torrent_auto/my_folder").unwrap();
+        std::fs::write(
+            "target/test_data/create_torrent_auto/my_folder/file.txt",
+            "This is a test file.",
+        )
+        .unwrap();
+        let path = super::create_torrent_file_auto(
+            "target/test_data/create_torrent_auto/my_folder",
+            "target/test_data/create_torrent_auto",
+            None,
+        )?;
+        assert_eq!(
+            path,
+            std::path::Path::new("target/test_data/create_torrent_auto/my_folder.torrent")
+        );
+        assert!(path.exists());
+        let written = std::fs::read(&path).unwrap();
+
+        // A second run must not overwrite the existing torrent.
+        let result = super::create_torrent_file_auto(
+            "target/test_data/create_torrent_auto/my_folder",
+            "target/test_data/create_torrent_auto",
+            None,
+        );
+        assert!(matches!(
+            result,
+            Err(super::BitTorrentError::FileSystem(msg)) if msg.contains("already exists")
+        ));
+        assert_eq!(std::fs::read(&path).unwrap(), written);
+        std::fs::remove_dir_all("target/test_data/create_torrent_auto").unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn io_error_keeps_kind() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "missing.torrent");
+        match super::BitTorrentError::from(err) {
+            super::BitTorrentError::FileSystem(msg) => {
+                assert_eq!(msg, "NotFound: missing.torrent")
+            }
+            other => panic!("Expected FileSystem error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bytes_remaining() {
+        let mut torrent = make_torrent(1, "abc123");
+        torrent.left_until_done = 123_456_789;
+        assert_eq!(torrent.bytes_remaining(), 123_456_789);
+    }
+
+    #[test]
+    fn availability_fraction() {
+        let availability = |desired_available, left_until_done| {
+            let mut torrent = make_torrent(1, "abc123");
+            torrent.desired_available = desired_available;
+            torrent.left_until_done = left_until_done;
+            torrent.availability_fraction()
+        };
+
+        assert_eq!(availability(0, 0), 1.0);
+        assert_eq!(availability(1000, 1000), 1.0);
+        assert_eq!(availability(250, 1000), 0.25);
+        assert_eq!(availability(0, 1000), 0.0);
+        assert_eq!(availability(2000, 1000), 1.0);
+    }
+
+    #[test]
+    fn torrent_summary() {
+        let mut torrent = make_torrent(7, "abc123");
+        torrent.name = "ubuntu.iso".to_string();
+        torrent.percent_done = 0.25;
+        torrent.rate_download = 1536 * 1024;
+        torrent.rate_upload = 512;
+
+        assert_eq!(
+            torrent.summary_table_row(),
+            [
+                "7",
+                "ubuntu.iso",
+                "25.0%",
+                "downloading",
+                "1.5 MiB/s",
+                "512 B/s"
+            ]
+        );
+        assert_eq!(
+            torrent.summary_line(),
+            "7 ubuntu.iso 25.0% downloading 1.5 MiB/s / 512 B/s"
+        );
+
+        torrent.status = 6;
+        torrent.percent_done = 1.0;
+        torrent.rate_download = 0;
+        torrent.rate_upload = 3 * 1024 * 1024 * 1024;
+        assert_eq!(
+            torrent.summary_line(),
+            "7 ubuntu.iso 100.0% seeding 0 B/s / 3.0 GiB/s"
+        );
+    }
+
+    #[test]
+    fn torrent_timestamps() {
+        use std::time::{Duration, SystemTime};
+
+        let mut torrent = make_torrent(1, "abc123");
+        assert_eq!(torrent.added_at(), None);
+
+        torrent.added_date = 1_700_000_000;
+        assert_eq!(
+            torrent.added_at(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+
+        torrent.start_date = -1;
+        assert_eq!(torrent.started_at(), None);
+
+        assert_eq!(torrent.changed_at(), torrent.added_at());
+        torrent.edit_date = 1_700_000_100;
+        torrent.activity_date = 1_700_000_050;
+        assert_eq!(torrent.changed_at(), torrent.edited_at());
+    }
+
+    #[tokio::test]
+    async fn create_torrent_async_matches_sync() -> Result<(), super::BitTorrentError> {
+        let dir = "target/test_data/create_torrent_async";
+        std::fs::create_dir_all(format!("{dir}/folder")).unwrap();
+        std::fs::write(format!("{dir}/folder/file.txt"), "This is a test file.").unwrap();
+        let tracker_url = Some("udp://tracker.opentrackr.org:1337/announce".to_string());
+
+        super::create_torrent_file(
+            &format!("{dir}/folder"),
+            &format!("{dir}/sync.torrent"),
+            tracker_url.clone(),
+        )?;
+        super::create_torrent_file_async(
+            &format!("{dir}/folder"),
+            &format!("{dir}/async.torrent"),
+            tracker_url,
+        )
+        .await?;
+
+        assert_eq!(
+            std::fs::read(format!("{dir}/sync.torrent")).unwrap(),
+            std::fs::read(format!("{dir}/async.torrent")).unwrap()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn torrent_active_dir() {
+        let mut torrent = make_torrent(1, "abc123");
+
+        assert_eq!(torrent.active_dir(Some("/incomplete")), "/incomplete");
+        assert_eq!(torrent.active_dir(None), "/downloads");
+
+        torrent.percent_done = 1.0;
+        assert_eq!(torrent.active_dir(Some("/incomplete")), "/downloads");
+    }
 }
@@ -0,0 +1,114 @@
+//! Descriptive metadata of `.torrent` files.
+
+use std::time::{Duration, SystemTime};
+
+use crate::BitTorrentError;
+use crate::validate::{as_integer, as_str, get, read_root};
+
+/// Who created a `.torrent` file and when, as returned by [`torrent_file_metadata`]. Every
+/// field is optional in the metainfo format, so missing keys are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentFileMeta {
+    /// The program that created the torrent (`created by`).
+    pub created_by: Option<String>,
+    /// When the torrent was created (`creation date`).
+    pub creation_date: Option<SystemTime>,
+    /// The free-form comment of the torrent.
+    pub comment: Option<String>,
+    /// The tracker announce URL.
+    pub announce: Option<String>,
+}
+
+/// Read the descriptive metadata from the top-level keys of a local `.torrent` file, without
+/// adding it to a client.
+///
+/// Keys that are present but of the wrong type are treated as missing. A non-positive
+/// `creation date` is treated as missing too.
+pub fn torrent_file_metadata(path: &str) -> Result<TorrentFileMeta, BitTorrentError> {
+    let root = read_root(path)?;
+    let string = |key| get(&root, key).and_then(as_str).map(str::to_owned);
+
+    Ok(TorrentFileMeta {
+        created_by: string("created by"),
+        creation_date: get(&root, "creation date")
+            .and_then(as_integer)
+            .and_then(|secs| u64::try_from(secs).ok())
+            .filter(|&secs| secs > 0)
+            .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+        comment: string("comment"),
+        announce: string("announce"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use lava_torrent::{bencode::BencodeElem, torrent::v1::Torrent as Metainfo};
+
+    use super::*;
+
+    const TRACKER_URL: &str = "udp://tracker.opentrackr.org:1337/announce";
+
+    /// Creates a torrent of a small folder in `dir` and returns its path.
+    fn create_torrent(dir: &str) -> String {
+        std::fs::create_dir_all(format!("{dir}/folder")).unwrap();
+        std::fs::write(format!("{dir}/folder/file.txt"), "This is a test file.").unwrap();
+        let output = format!("{dir}/folder.torrent");
+        crate::create_torrent_file(
+            &format!("{dir}/folder"),
+            &output,
+            Some(TRACKER_URL.to_string()),
+        )
+        .unwrap();
+        output
+    }
+
+    #[test]
+    fn metadata_without_comment() -> Result<(), BitTorrentError> {
+        let dir = "target/test_data/torrent_file_metadata";
+        let torrent = create_torrent(dir);
+
+        let meta = torrent_file_metadata(&torrent)?;
+        assert_eq!(meta.announce.as_deref(), Some(TRACKER_URL));
+        assert_eq!(meta.comment, None);
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn metadata_with_comment() -> Result<(), BitTorrentError> {
+        let dir = "target/test_data/torrent_file_metadata_comment";
+        let torrent = create_torrent(dir);
+
+        let mut metainfo = Metainfo::read_from_file(&torrent).unwrap();
+        metainfo.extra_fields = Some(HashMap::from([
+            (
+                "comment".to_string(),
+                BencodeElem::String("test comment".to_string()),
+            ),
+            (
+                "created by".to_string(),
+                BencodeElem::String("mosaic".to_string()),
+            ),
+            (
+                "creation date".to_string(),
+                BencodeElem::Integer(1_700_000_000),
+            ),
+        ]));
+        metainfo.write_into_file(&torrent).unwrap();
+
+        let meta = torrent_file_metadata(&torrent)?;
+        assert_eq!(
+            meta,
+            TorrentFileMeta {
+                created_by: Some("mosaic".to_string()),
+                creation_date: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+                comment: Some("test comment".to_string()),
+                announce: Some(TRACKER_URL.to_string()),
+            }
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+//! Structural validation of `.torrent` files.
+//!
+//! [`Metainfo::read_from_file`](lava_torrent::torrent::v1::Torrent::read_from_file) only reports
+//! that a file is malformed, not why. To point at the exact problem, the bencode is decoded and
+//! the metainfo dictionary is checked key by key here instead.
+
+use lava_torrent::bencode::BencodeElem;
+
+use crate::BitTorrentError;
+
+/// Length of a single SHA-1 piece hash in the `pieces` string.
+const PIECE_HASH_LENGTH: usize = 20;
+
+/// A summary of a `.torrent` file, as returned by [`validate_torrent_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentSummary {
+    /// The suggested name of the torrent's file or folder.
+    pub name: String,
+    /// The total size in bytes of the torrent's content.
+    pub total_size: i64,
+    /// The number of files in the torrent.
+    pub file_count: usize,
+    /// The number of pieces in the torrent.
+    pub piece_count: usize,
+    /// Whether the torrent is private, i.e. restricted to its trackers.
+    pub private: bool,
+}
+
+/// Validate the structure of a local `.torrent` file and summarize it.
+///
+/// Fails with [`BitTorrentError::InvalidTorrent`] describing the first structural problem found,
+/// e.g. a missing `info` dictionary or a `piece length` that does not match the `pieces`.
+pub fn validate_torrent_file(path: &str) -> Result<TorrentSummary, BitTorrentError> {
+    let root = read_root(path)?;
+    let info = get(&root, "info").ok_or_else(|| invalid("missing `info` dictionary"))?;
+    if !is_dictionary(info) {
+        return Err(invalid("`info` is not a dictionary"));
+    }
+
+    let name = get(info, "name")
+        .and_then(as_str)
+        .ok_or_else(|| invalid("missing or invalid `name` in `info`"))?
+        .to_owned();
+
+    let piece_length = get(info, "piece length")
+        .and_then(as_integer)
+        .ok_or_else(|| invalid("missing or invalid `piece length` in `info`"))?;
+    if piece_length <= 0 {
+        return Err(invalid(format!(
+            "`piece length` must be positive, found {}",
+            piece_length
+        )));
+    }
+
+    let pieces = get(info, "pieces")
+        .and_then(as_bytes)
+        .ok_or_else(|| invalid("missing or invalid `pieces` in `info`"))?;
+    if pieces.is_empty() || pieces.len() % PIECE_HASH_LENGTH != 0 {
+        return Err(invalid(format!(
+            "`pieces` length {} is not a positive multiple of {}",
+            pieces.len(),
+            PIECE_HASH_LENGTH
+        )));
+    }
+    let piece_count = pieces.len() / PIECE_HASH_LENGTH;
+
+    let (total_size, file_count) = match (get(info, "length"), get(info, "files")) {
+        (Some(_), Some(_)) => {
+            return Err(invalid("`info` has both `length` and `files`"));
+        }
+        (Some(length), None) => (file_length(length, "`length`")?, 1),
+        (None, Some(files)) => files_size(files)?,
+        (None, None) => return Err(invalid("`info` has neither `length` nor `files`")),
+    };
+
+    // Both are known to be non-negative here.
+    let expected_pieces = (total_size as u64).div_ceil(piece_length as u64);
+    if expected_pieces != piece_count as u64 {
+        return Err(invalid(format!(
+            "`piece length` {} does not match the content: {} bytes need {} pieces, found {}",
+            piece_length, total_size, expected_pieces, piece_count
+        )));
+    }
+
+    let private = get(info, "private").and_then(as_integer) == Some(1);
+
+    Ok(TorrentSummary {
+        name,
+        total_size,
+        file_count,
+        piece_count,
+        private,
+    })
+}
+
+/// Reads a `.torrent` file and decodes its top-level dictionary.
+pub(crate) fn read_root(path: &str) -> Result<BencodeElem, BitTorrentError> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| BitTorrentError::FileSystem(format!("failed to read {}: {}", path, e)))?;
+    let mut elements = BencodeElem::from_bytes(&bytes)
+        .map_err(|e| invalid(format!("not valid bencode: {}", e)))?;
+    if elements.len() != 1 {
+        return Err(invalid(format!(
+            "expected a single top-level dictionary, found {} elements",
+            elements.len()
+        )));
+    }
+    let root = elements.remove(0);
+    if !is_dictionary(&root) {
+        return Err(invalid("top-level element is not a dictionary"));
+    }
+
+    Ok(root)
+}
+
+/// Sums up the lengths of a multi-file `files` list, returning the total size and file count.
+fn files_size(files: &BencodeElem) -> Result<(i64, usize), BitTorrentError> {
+    let BencodeElem::List(files) = files else {
+        return Err(invalid("`files` is not a list"));
+    };
+    if files.is_empty() {
+        return Err(invalid("`files` is empty"));
+    }
+
+    let mut total_size = 0i64;
+    for (index, file) in files.iter().enumerate() {
+        let length = get(file, "length")
+            .ok_or_else(|| invalid(format!("missing `length` in file {}", index)))?;
+        total_size = total_size
+            .checked_add(file_length(length, &format!("`length` of file {}", index))?)
+            .ok_or_else(|| invalid(format!("total size overflows at file {}", index)))?;
+
+        let path = match get(file, "path") {
+            Some(BencodeElem::List(path)) => path,
+            _ => {
+                return Err(invalid(format!(
+                    "missing or invalid `path` in file {}",
+                    index
+                )));
+            }
+        };
+        if path.is_empty() || path.iter().any(|component| as_str(component).is_none()) {
+            return Err(invalid(format!("invalid `path` in file {}", index)));
+        }
+    }
+
+    Ok((total_size, files.len()))
+}
+
+/// Reads a file length, which must be a non-negative integer.
+fn file_length(length: &BencodeElem, what: &str) -> Result<i64, BitTorrentError> {
+    match as_integer(length) {
+        Some(length) if length >= 0 => Ok(length),
+        _ => Err(invalid(format!("{} is not a non-negative integer", what))),
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> BitTorrentError {
+    BitTorrentError::InvalidTorrent(msg.into())
+}
+
+fn is_dictionary(elem: &BencodeElem) -> bool {
+    matches!(
+        elem,
+        BencodeElem::Dictionary(_) | BencodeElem::RawDictionary(_)
+    )
+}
+
+/// Looks up `key` in a dictionary, whether or not its keys are valid UTF-8.
+pub(crate) fn get<'a>(dict: &'a BencodeElem, key: &str) -> Option<&'a BencodeElem> {
+    match dict {
+        BencodeElem::Dictionary(dict) => dict.get(key),
+        BencodeElem::RawDictionary(dict) => dict.get(key.as_bytes()),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_integer(elem: &BencodeElem) -> Option<i64> {
+    match elem {
+        BencodeElem::Integer(value) => Some(*value),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_str(elem: &BencodeElem) -> Option<&str> {
+    match elem {
+        BencodeElem::String(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Reads a byte string. Byte strings that happen to be valid UTF-8 are decoded as strings.
+fn as_bytes(elem: &BencodeElem) -> Option<&[u8]> {
+    match elem {
+        BencodeElem::Bytes(value) => Some(value),
+        BencodeElem::String(value) => Some(value.as_bytes()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORRUPT_DIR: &str = "target/test_data/validate_torrent_corrupt";
+
+    /// Writes raw bencode to a file in the test directory and validates it.
+    fn validate_raw(name: &str, bencode: &[u8]) -> Result<TorrentSummary, BitTorrentError> {
+        std::fs::create_dir_all(CORRUPT_DIR).unwrap();
+        let path = format!("{CORRUPT_DIR}/{name}.torrent");
+        std::fs::write(&path, bencode).unwrap();
+        validate_torrent_file(&path)
+    }
+
+    fn assert_invalid(result: Result<TorrentSummary, BitTorrentError>, expected: &str) {
+        match result {
+            Err(BitTorrentError::InvalidTorrent(msg)) => {
+                assert!(msg.contains(expected), "unexpected message: {msg}")
+            }
+            other => panic!("Expected InvalidTorrent error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn validate_generated_torrent() -> Result<(), BitTorrentError> {
+        let folder = "target/test_data/validate_torrent/folder";
+        std::fs::create_dir_all(folder).unwrap();
+        std::fs::write(format!("{folder}/a.txt"), "12345").unwrap();
+        std::fs::write(format!("{folder}/b.txt"), "1234567890").unwrap();
+        let output = "target/test_data/validate_torrent/folder.torrent";
+        crate::create_torrent_file(folder, output, None)?;
+
+        let summary = validate_torrent_file(output)?;
+        assert_eq!(
+            summary,
+            TorrentSummary {
+                name: "folder".to_string(),
+                total_size: 15,
+                file_count: 2,
+                piece_count: 1,
+                private: false,
+            }
+        );
+        std::fs::remove_dir_all("target/test_data/validate_torrent").unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn validate_corrupt_torrents() {
+        let pieces = [b'x'; PIECE_HASH_LENGTH];
+        let with_info = |info: &[u8]| [b"d4:info".as_slice(), info, b"e"].concat();
+
+        assert_invalid(validate_raw("not_bencode", b"not bencode"), "bencode");
+        assert_invalid(
+            validate_raw("no_info", b"d8:announce3:fooe"),
+            "missing `info`",
+        );
+        assert_invalid(
+            validate_raw(
+                "no_pieces",
+                &with_info(b"d6:lengthi5e4:name1:a12:piece lengthi16384ee"),
+            ),
+            "`pieces`",
+        );
+        assert_invalid(
+            validate_raw(
+                "zero_piece_length",
+                &with_info(
+                    &[
+                        b"d6:lengthi5e4:name1:a12:piece lengthi0e6:pieces20:".as_slice(),
+                        &pieces,
+                        b"e",
+                    ]
+                    .concat(),
+                ),
+            ),
+            "`piece length` must be positive",
+        );
+        assert_invalid(
+            validate_raw(
+                "wrong_piece_length",
+                &with_info(
+                    &[
+                        b"d6:lengthi40000e4:name1:a12:piece lengthi16384e6:pieces20:".as_slice(),
+                        &pieces,
+                        b"e",
+                    ]
+                    .concat(),
+                ),
+            ),
+            "does not match the content",
+        );
+        assert_invalid(
+            validate_raw(
+                "no_length",
+                &with_info(
+                    &[
+                        b"d4:name1:a12:piece lengthi16384e6:pieces20:".as_slice(),
+                        &pieces,
+                        b"e",
+                    ]
+                    .concat(),
+                ),
+            ),
+            "neither `length` nor `files`",
+        );
+        assert_invalid(
+            validate_raw(
+                "size_overflow",
+                &with_info(
+                    &[
+                        b"d5:filesl".as_slice(),
+                        b"d6:lengthi9223372036854775000e4:pathl1:aee",
+                        b"d6:lengthi9223372036854775000e4:pathl1:bee",
+                        b"e4:name1:a12:piece lengthi16384e6:pieces20:",
+                        &pieces,
+                        b"e",
+                    ]
+                    .concat(),
+                ),
+            ),
+            "total size overflows at file 1",
+        );
+        std::fs::remove_dir_all(CORRUPT_DIR).unwrap();
+    }
+}